@@ -0,0 +1,4 @@
+//! Shared and static libraries exporting the C interface of `sha1::ffi`, declared in
+//! `include/sha1.h`.
+
+pub use sha1::ffi::*;
@@ -0,0 +1,59 @@
+/* C interface to the sha1 crate, built with the `ffi` feature. Generated from src/ffi.rs by cbindgen with cbindgen.toml; do not edit. */
+
+#ifndef SHA1_H
+#define SHA1_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct sha1_ctx sha1_ctx;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a new hash context. It must be passed to `sha1_finish()` or `sha1_free()` to be
+ * freed.
+ */
+sha1_ctx *sha1_new(void);
+
+/**
+ * Adds `len` bytes from `data` to the hash.
+ *
+ * # Safety
+ * `ctx` must come from `sha1_new()` and not be freed yet. `data` must point to `len` readable
+ * bytes, or may be null if `len` is 0.
+ */
+void sha1_update(sha1_ctx *ctx, const uint8_t *data, size_t len);
+
+/**
+ * Writes the 20 byte hash to `out` and frees the context.
+ *
+ * # Safety
+ * `ctx` must come from `sha1_new()` and not be freed yet. `out` must point to 20 writable bytes.
+ */
+void sha1_finish(sha1_ctx *ctx, uint8_t *out);
+
+/**
+ * Frees a context without finishing it. Does nothing if `ctx` is null.
+ *
+ * # Safety
+ * `ctx` must be null, or come from `sha1_new()` and not be freed yet.
+ */
+void sha1_free(sha1_ctx *ctx);
+
+/**
+ * Hashes `len` bytes from `data` and writes the 20 byte hash to `out`.
+ *
+ * # Safety
+ * `data` must point to `len` readable bytes, or may be null if `len` is 0. `out` must point to
+ * 20 writable bytes.
+ */
+void sha1_oneshot(const uint8_t *data, size_t len, uint8_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SHA1_H */
@@ -0,0 +1,63 @@
+// Read-ahead hints for files that are about to be read sequentially from start to end, so that the
+// kernel reads further ahead and doesn't let them push more useful data out of the page cache.
+// Hints are only advice, so failures are ignored.
+
+use std::fs::File;
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+))]
+use std::os::unix::io::AsRawFd;
+
+// Bytes that macOS is asked to start reading right away
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const PREFETCH_LEN: u64 = 4 << 20;
+
+// Advises the kernel that len bytes from offset, or everything from offset if len is 0, are going
+// to be read once, in order.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn sequential(file: &File, offset: u64, len: u64) {
+    let fd = file.as_raw_fd();
+    let offset = offset as libc::off_t;
+    let len = len.min(libc::off_t::MAX as u64) as libc::off_t;
+
+    // Safety: the descriptor is open for as long as file is borrowed
+    unsafe {
+        libc::posix_fadvise(fd, offset, len, libc::POSIX_FADV_SEQUENTIAL);
+        // Linux only acts on NOREUSE for reads made after it, so it's given up front rather than
+        // once hashing is done
+        libc::posix_fadvise(fd, offset, len, libc::POSIX_FADV_NOREUSE);
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub(crate) fn sequential(file: &File, offset: u64, len: u64) {
+    let fd = file.as_raw_fd();
+    let count = if len == 0 {
+        PREFETCH_LEN
+    } else {
+        len.min(PREFETCH_LEN)
+    };
+    let advice = libc::radvisory {
+        ra_offset: offset as libc::off_t,
+        ra_count: count as libc::c_int,
+    };
+
+    // Safety: the descriptor is open for as long as file is borrowed, and advice outlives the
+    // call
+    unsafe {
+        libc::fcntl(fd, libc::F_RDAHEAD, 1);
+        libc::fcntl(fd, libc::F_RDADVISE, &advice as *const libc::radvisory);
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios"
+)))]
+pub(crate) fn sequential(_file: &File, _offset: u64, _len: u64) {}
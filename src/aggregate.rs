@@ -0,0 +1,130 @@
+use crate::{Digest, Hash, Sha1};
+use core::iter::FromIterator;
+
+/// Fingerprint of an unordered collection of items, which can be updated as items are added or
+/// removed without hashing the whole collection again.
+///
+/// Each item is hashed on its own, and the hashes are added together as 160 bit numbers modulo
+/// 2^160. Addition doesn't depend on order, so two collections with the same items, added in any
+/// order, have the same fingerprint. Items can appear more than once, and each copy counts.
+///
+/// Unlike a hash of the items concatenated in a sorted order, anyone who can choose many items
+/// can find a different collection with the same fingerprint far faster than a SHA-1 collision.
+/// It should only be used to tell replicas of trusted data apart, not to authenticate data.
+///
+/// Example usage:
+/// ```
+/// # use sha1::AggregateDigest;
+/// let mut a = AggregateDigest::new();
+/// a.insert(b"apple");
+/// a.insert(b"banana");
+///
+/// let mut b = AggregateDigest::new();
+/// b.insert(b"banana");
+/// b.insert(b"cherry");
+/// b.insert(b"apple");
+/// b.remove(b"cherry");
+///
+/// assert_eq!(a, b);
+/// assert_eq!(a.digest(), b.digest());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AggregateDigest {
+    sum: Hash,
+    len: u64,
+}
+
+impl AggregateDigest {
+    /// Creates the fingerprint of an empty collection.
+    pub const fn new() -> AggregateDigest {
+        AggregateDigest {
+            sum: [0; 5],
+            len: 0,
+        }
+    }
+
+    /// Hashes an item and adds it to the collection.
+    pub fn insert<D: AsRef<[u8]>>(&mut self, item: D) {
+        self.insert_digest(Sha1::digest(item));
+    }
+
+    /// Removes an item from the collection. Removing an item that was never added leaves a
+    /// fingerprint that no collection has, until the item is added back.
+    pub fn remove<D: AsRef<[u8]>>(&mut self, item: D) {
+        self.remove_digest(Sha1::digest(item));
+    }
+
+    /// Adds an item that has already been hashed to the collection.
+    pub fn insert_digest<H: Into<Hash>>(&mut self, hash: H) {
+        self.sum = add(self.sum, hash.into());
+        self.len = self.len.wrapping_add(1);
+    }
+
+    /// Removes an item that has already been hashed from the collection. See
+    /// `AggregateDigest::remove()`.
+    pub fn remove_digest<H: Into<Hash>>(&mut self, hash: H) {
+        self.sum = add(self.sum, negate(hash.into()));
+        self.len = self.len.wrapping_sub(1);
+    }
+
+    /// Adds every item of another collection to this one, as if they had been inserted one by
+    /// one.
+    pub fn merge(&mut self, other: &AggregateDigest) {
+        self.sum = add(self.sum, other.sum);
+        self.len = self.len.wrapping_add(other.len);
+    }
+
+    /// Returns the number of items in the collection.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns whether the collection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the fingerprint of the collection, which is the sum of the hashes of its items.
+    /// Collections of different sizes can have the same sum, so comparing whole
+    /// `AggregateDigest`s also compares their sizes.
+    pub fn digest(&self) -> Digest {
+        Digest::from(self.sum)
+    }
+}
+
+impl<D: AsRef<[u8]>> Extend<D> for AggregateDigest {
+    /// Hashes and adds every item.
+    fn extend<I: IntoIterator<Item = D>>(&mut self, items: I) {
+        for item in items {
+            self.insert(item);
+        }
+    }
+}
+
+impl<D: AsRef<[u8]>> FromIterator<D> for AggregateDigest {
+    /// Creates the fingerprint of a collection of items.
+    fn from_iter<I: IntoIterator<Item = D>>(items: I) -> AggregateDigest {
+        let mut aggregate = AggregateDigest::new();
+        aggregate.extend(items);
+        aggregate
+    }
+}
+
+// Adds two hashes as 160 bit big-endian numbers, modulo 2^160
+fn add(a: Hash, b: Hash) -> Hash {
+    let mut sum = [0; 5];
+    let mut carry = 0;
+
+    for i in (0..5).rev() {
+        let word = a[i] as u64 + b[i] as u64 + carry;
+        sum[i] = word as u32;
+        carry = word >> 32;
+    }
+
+    sum
+}
+
+// Returns the number that adds to hash to give 0 modulo 2^160
+fn negate(hash: Hash) -> Hash {
+    add(hash.map(|word| !word), [0, 0, 0, 0, 1])
+}
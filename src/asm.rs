@@ -0,0 +1,207 @@
+// SHA-1 compression in x86_64 assembly using the SHA extensions, with the instruction sequence
+// from Intel's paper on them. Only compiled with the asm feature when the sha and sse4.1 target
+// features are enabled, e.g. with RUSTFLAGS="-C target-cpu=native" on a CPU that has them.
+
+use crate::Hash;
+use core::arch::asm;
+
+// Reverses all 16 bytes of a vector, which byte-swaps each big-endian word and puts the words in
+// the order that sha1rnds4 expects
+static BSWAP_MASK: [u8; 16] = [15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0];
+
+pub(crate) fn compress(mut h: Hash, chunk: &[u8; 64]) -> Hash {
+    // Safety: reads and writes the 20 bytes of h, and reads the 64 bytes of chunk and the 16 of
+    // the mask. The SHA and SSE4.1 instructions are available since their target features are
+    // enabled.
+    unsafe {
+        asm!(
+            // Load the state, with A in the highest word of abcd and E in the highest word of e0
+            "movdqu {abcd}, [{state}]",
+            "pshufd {abcd}, {abcd}, 0x1B",
+            "movd {e0}, dword ptr [{state} + 16]",
+            "pslldq {e0}, 12",
+            "movdqu {mask}, [{mask_ptr}]",
+            "movdqa {abcd_save}, {abcd}",
+            "movdqa {e0_save}, {e0}",
+
+            // Rounds 0-3
+            "movdqu {m0}, [{block}]",
+            "pshufb {m0}, {mask}",
+            "paddd {e0}, {m0}",
+            "movdqa {e1}, {abcd}",
+            "sha1rnds4 {abcd}, {e0}, 0",
+
+            // Rounds 4-7
+            "movdqu {m1}, [{block} + 16]",
+            "pshufb {m1}, {mask}",
+            "sha1nexte {e1}, {m1}",
+            "movdqa {e0}, {abcd}",
+            "sha1rnds4 {abcd}, {e1}, 0",
+            "sha1msg1 {m0}, {m1}",
+
+            // Rounds 8-11
+            "movdqu {m2}, [{block} + 32]",
+            "pshufb {m2}, {mask}",
+            "sha1nexte {e0}, {m2}",
+            "movdqa {e1}, {abcd}",
+            "sha1rnds4 {abcd}, {e0}, 0",
+            "sha1msg1 {m1}, {m2}",
+            "pxor {m0}, {m2}",
+
+            // Rounds 12-15
+            "movdqu {m3}, [{block} + 48]",
+            "pshufb {m3}, {mask}",
+            "sha1nexte {e1}, {m3}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m0}, {m3}",
+            "sha1rnds4 {abcd}, {e1}, 0",
+            "sha1msg1 {m2}, {m3}",
+            "pxor {m1}, {m3}",
+
+            // Rounds 16-19
+            "sha1nexte {e0}, {m0}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m1}, {m0}",
+            "sha1rnds4 {abcd}, {e0}, 0",
+            "sha1msg1 {m3}, {m0}",
+            "pxor {m2}, {m0}",
+
+            // Rounds 20-23
+            "sha1nexte {e1}, {m1}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m2}, {m1}",
+            "sha1rnds4 {abcd}, {e1}, 1",
+            "sha1msg1 {m0}, {m1}",
+            "pxor {m3}, {m1}",
+
+            // Rounds 24-27
+            "sha1nexte {e0}, {m2}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m3}, {m2}",
+            "sha1rnds4 {abcd}, {e0}, 1",
+            "sha1msg1 {m1}, {m2}",
+            "pxor {m0}, {m2}",
+
+            // Rounds 28-31
+            "sha1nexte {e1}, {m3}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m0}, {m3}",
+            "sha1rnds4 {abcd}, {e1}, 1",
+            "sha1msg1 {m2}, {m3}",
+            "pxor {m1}, {m3}",
+
+            // Rounds 32-35
+            "sha1nexte {e0}, {m0}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m1}, {m0}",
+            "sha1rnds4 {abcd}, {e0}, 1",
+            "sha1msg1 {m3}, {m0}",
+            "pxor {m2}, {m0}",
+
+            // Rounds 36-39
+            "sha1nexte {e1}, {m1}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m2}, {m1}",
+            "sha1rnds4 {abcd}, {e1}, 1",
+            "sha1msg1 {m0}, {m1}",
+            "pxor {m3}, {m1}",
+
+            // Rounds 40-43
+            "sha1nexte {e0}, {m2}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m3}, {m2}",
+            "sha1rnds4 {abcd}, {e0}, 2",
+            "sha1msg1 {m1}, {m2}",
+            "pxor {m0}, {m2}",
+
+            // Rounds 44-47
+            "sha1nexte {e1}, {m3}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m0}, {m3}",
+            "sha1rnds4 {abcd}, {e1}, 2",
+            "sha1msg1 {m2}, {m3}",
+            "pxor {m1}, {m3}",
+
+            // Rounds 48-51
+            "sha1nexte {e0}, {m0}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m1}, {m0}",
+            "sha1rnds4 {abcd}, {e0}, 2",
+            "sha1msg1 {m3}, {m0}",
+            "pxor {m2}, {m0}",
+
+            // Rounds 52-55
+            "sha1nexte {e1}, {m1}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m2}, {m1}",
+            "sha1rnds4 {abcd}, {e1}, 2",
+            "sha1msg1 {m0}, {m1}",
+            "pxor {m3}, {m1}",
+
+            // Rounds 56-59
+            "sha1nexte {e0}, {m2}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m3}, {m2}",
+            "sha1rnds4 {abcd}, {e0}, 2",
+            "sha1msg1 {m1}, {m2}",
+            "pxor {m0}, {m2}",
+
+            // Rounds 60-63
+            "sha1nexte {e1}, {m3}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m0}, {m3}",
+            "sha1rnds4 {abcd}, {e1}, 3",
+            "sha1msg1 {m2}, {m3}",
+            "pxor {m1}, {m3}",
+
+            // Rounds 64-67
+            "sha1nexte {e0}, {m0}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m1}, {m0}",
+            "sha1rnds4 {abcd}, {e0}, 3",
+            "sha1msg1 {m3}, {m0}",
+            "pxor {m2}, {m0}",
+
+            // Rounds 68-71
+            "sha1nexte {e1}, {m1}",
+            "movdqa {e0}, {abcd}",
+            "sha1msg2 {m2}, {m1}",
+            "sha1rnds4 {abcd}, {e1}, 3",
+            "pxor {m3}, {m1}",
+
+            // Rounds 72-75
+            "sha1nexte {e0}, {m2}",
+            "movdqa {e1}, {abcd}",
+            "sha1msg2 {m3}, {m2}",
+            "sha1rnds4 {abcd}, {e0}, 3",
+
+            // Rounds 76-79
+            "sha1nexte {e1}, {m3}",
+            "movdqa {e0}, {abcd}",
+            "sha1rnds4 {abcd}, {e1}, 3",
+
+            // Add the compressed block to the state and store it
+            "sha1nexte {e0}, {e0_save}",
+            "paddd {abcd}, {abcd_save}",
+            "pshufd {abcd}, {abcd}, 0x1B",
+            "movdqu [{state}], {abcd}",
+            "pextrd dword ptr [{state} + 16], {e0}, 3",
+            state = in(reg) h.as_mut_ptr(),
+            block = in(reg) chunk.as_ptr(),
+            mask_ptr = in(reg) BSWAP_MASK.as_ptr(),
+            abcd = out(xmm_reg) _,
+            e0 = out(xmm_reg) _,
+            e1 = out(xmm_reg) _,
+            m0 = out(xmm_reg) _,
+            m1 = out(xmm_reg) _,
+            m2 = out(xmm_reg) _,
+            m3 = out(xmm_reg) _,
+            mask = out(xmm_reg) _,
+            abcd_save = out(xmm_reg) _,
+            e0_save = out(xmm_reg) _,
+            options(nostack, preserves_flags),
+        );
+    }
+
+    h
+}
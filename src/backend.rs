@@ -0,0 +1,98 @@
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Implementation of the compression function that hashing runs on. Returned by
+/// `Sha1::active_backend()`.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// Plain Rust, available on every target.
+    Portable,
+    /// x86_64 assembly using the SHA extensions, used with the `asm` feature when built with the
+    /// `sha` and `sse4.1` target features.
+    X86ShaAsm,
+    /// wasm32 simd128 instructions, used when built with `-C target-feature=+simd128`.
+    WasmSimd128,
+    /// `std::simd` vector operations, used with the `portable-simd` feature on targets other than
+    /// x86 and ARM, where the portable backend is faster.
+    PortableSimd,
+}
+
+impl fmt::Display for Backend {
+    /// Formats the backend's name, e.g. for bug reports.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Backend::Portable => "portable",
+            Backend::X86ShaAsm => "x86_64 SHA extensions assembly",
+            Backend::WasmSimd128 => "wasm32 simd128",
+            Backend::PortableSimd => "portable simd",
+        })
+    }
+}
+
+// Whether the portable backend is used even if a faster one is available. Starts out UNSET, and
+// is decided from the SHA1_FORCE_SOFTWARE environment variable when first needed. Without std,
+// there are no environment variables to check.
+const UNSET: u8 = 0;
+const AUTO: u8 = 1;
+const FORCED: u8 = 2;
+static MODE: AtomicU8 = AtomicU8::new(UNSET);
+
+pub(crate) fn force_portable(force: bool) {
+    MODE.store(if force { FORCED } else { AUTO }, Ordering::Relaxed);
+}
+
+fn forced() -> bool {
+    let mut mode = MODE.load(Ordering::Relaxed);
+    if mode == UNSET {
+        #[cfg(feature = "std")]
+        let force =
+            std::env::var_os("SHA1_FORCE_SOFTWARE").is_some_and(|v| !v.is_empty() && v != "0");
+        #[cfg(not(feature = "std"))]
+        let force = false;
+        let new = if force { FORCED } else { AUTO };
+
+        // Don't overwrite a mode set with force_portable() in the meantime
+        mode = match MODE.compare_exchange(UNSET, new, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => new,
+            Err(current) => current,
+        };
+    }
+
+    mode == FORCED
+}
+
+// Fastest backend that this target was built with
+pub(crate) const fn best() -> Backend {
+    if cfg!(all(
+        feature = "asm",
+        target_arch = "x86_64",
+        target_feature = "sha",
+        target_feature = "sse4.1"
+    )) {
+        Backend::X86ShaAsm
+    } else if cfg!(all(target_arch = "wasm32", target_feature = "simd128")) {
+        Backend::WasmSimd128
+    } else if cfg!(all(
+        feature = "portable-simd",
+        not(any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64"
+        ))
+    )) {
+        Backend::PortableSimd
+    } else {
+        Backend::Portable
+    }
+}
+
+#[inline]
+pub(crate) fn active() -> Backend {
+    match best() {
+        Backend::Portable => Backend::Portable,
+        _ if forced() => Backend::Portable,
+        best => best,
+    }
+}
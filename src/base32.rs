@@ -0,0 +1,27 @@
+// Base32 encoding (RFC 4648).
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// Encodes bytes with the standard alphabet, optionally padding the output with '='.
+pub(crate) fn encode(data: &[u8], pad: bool) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+
+    for group in data.chunks(5) {
+        let mut b = [0; 8];
+        b[3..3 + group.len()].copy_from_slice(group);
+        let n = u64::from_be_bytes(b);
+
+        // Number of characters needed to hold the group's bits
+        let chars = (group.len() * 8).div_ceil(5);
+
+        for i in 0..8 {
+            if i < chars {
+                out.push(ALPHABET[(n >> (35 - 5 * i) & 0x1f) as usize] as char);
+            } else if pad {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
@@ -0,0 +1,201 @@
+//! `sha1sum` compatible command line tool, built with the `cli` feature.
+
+use sha1::manifest::{Entry, Format, Manifest, Status};
+use sha1::{Hash, Sha1, Sha1Error};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::process;
+
+const USAGE: &str = "\
+Usage: sha1sum [OPTION]... [FILE]...
+Print or check SHA1 checksums. With no FILE, or when FILE is -, read standard input.
+
+  -b, --binary  mark files as binary in GNU style output
+  -c, --check   read checksums from the FILEs and check them
+      --tag     create BSD style checksums
+  -h, --help    display this help and exit";
+
+#[derive(Default)]
+struct Options {
+    binary: bool,
+    check: bool,
+    tag: bool,
+    files: Vec<String>,
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("sha1sum: {}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let ok = if options.check {
+        check(&options.files)
+    } else {
+        print_sums(&options)
+    };
+
+    process::exit(if ok { 0 } else { 1 });
+}
+
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut only_files = false;
+
+    for arg in args {
+        if only_files || arg == "-" || !arg.starts_with('-') {
+            options.files.push(arg);
+            continue;
+        }
+
+        match arg.as_str() {
+            "--" => only_files = true,
+            "--binary" => options.binary = true,
+            "--check" => options.check = true,
+            "--tag" => options.tag = true,
+            "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unrecognized option '{}'", arg)),
+
+            // Bundled short options, like -bc
+            _ => {
+                for c in arg.chars().skip(1) {
+                    match c {
+                        'b' => options.binary = true,
+                        'c' => options.check = true,
+                        'h' => {
+                            println!("{}", USAGE);
+                            process::exit(0);
+                        }
+                        _ => return Err(format!("invalid option -- '{}'", c)),
+                    }
+                }
+            }
+        }
+    }
+
+    if options.files.is_empty() {
+        options.files.push("-".to_string());
+    }
+
+    Ok(options)
+}
+
+// Hashes a file, or standard input if the path is "-".
+fn hash_path(path: &str) -> Result<Hash, Sha1Error> {
+    let (hash, _) = if path == "-" {
+        Sha1::digest_reader(io::stdin().lock())?
+    } else {
+        Sha1::digest_path(path)?
+    };
+
+    Ok(hash)
+}
+
+fn print_sums(options: &Options) -> bool {
+    let mut ok = true;
+
+    let format = match (options.tag, options.binary) {
+        (true, _) => Format::Bsd,
+        (false, true) => Format::GnuBinary,
+        (false, false) => Format::Gnu,
+    };
+
+    for path in &options.files {
+        match hash_path(path) {
+            Ok(hash) => {
+                let path = path.clone();
+                println!("{}", Entry { hash, path, format });
+            }
+            Err(e) => {
+                eprintln!("sha1sum: {}: {}", path, e);
+                ok = false;
+            }
+        }
+    }
+
+    ok
+}
+
+fn check(files: &[String]) -> bool {
+    let mut bad_lines = 0;
+    let mut unreadable = 0;
+    let mut mismatched = 0;
+
+    for file in files {
+        let manifest = if file == "-" {
+            Manifest::read(io::stdin().lock())
+        } else {
+            File::open(file)
+                .map_err(Sha1Error::from)
+                .and_then(|f| Manifest::read(BufReader::new(f)))
+        };
+
+        let manifest = match manifest {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                eprintln!("sha1sum: {}: {}", file, e);
+                unreadable += 1;
+                continue;
+            }
+        };
+
+        bad_lines += manifest.bad_lines.len();
+
+        if manifest.entries.is_empty() {
+            eprintln!(
+                "sha1sum: {}: no properly formatted checksum lines found",
+                file
+            );
+            unreadable += 1;
+        }
+
+        for entry in &manifest.entries {
+            match entry.verify("") {
+                Status::Ok => println!("{}: OK", entry.path),
+                Status::Mismatch(_) => {
+                    println!("{}: FAILED", entry.path);
+                    mismatched += 1;
+                }
+                Status::Missing => {
+                    eprintln!("sha1sum: {}: No such file or directory", entry.path);
+                    println!("{}: FAILED open or read", entry.path);
+                    unreadable += 1;
+                }
+                Status::Error(e) => {
+                    eprintln!("sha1sum: {}: {}", entry.path, e);
+                    println!("{}: FAILED open or read", entry.path);
+                    unreadable += 1;
+                }
+            }
+        }
+    }
+
+    if bad_lines > 0 {
+        eprintln!(
+            "sha1sum: WARNING: {} line(s) are improperly formatted",
+            bad_lines
+        );
+    }
+    if unreadable > 0 {
+        eprintln!(
+            "sha1sum: WARNING: {} listed file(s) could not be read",
+            unreadable
+        );
+    }
+    if mismatched > 0 {
+        eprintln!(
+            "sha1sum: WARNING: {} computed checksum(s) did NOT match",
+            mismatched
+        );
+    }
+
+    // Like GNU sha1sum, improperly formatted lines only produce a warning
+    unreadable == 0 && mismatched == 0
+}
@@ -0,0 +1,177 @@
+//! Content-addressable storage of blobs on disk, named by their hashes.
+//!
+//! Blobs are stored with the same fan-out layout as git's loose objects: a blob with the hash
+//! `abcdef...` is stored as `ab/cdef...` under the store's root directory, so that no single
+//! directory grows too large.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::cas::Store;
+//! # fn f() -> Result<(), sha1::Sha1Error> {
+//! let store = Store::open("blobs")?;
+//! let digest = store.put(b"Hello, world")?;
+//! assert_eq!(store.get(&digest)?, b"Hello, world");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{hex, Digest, Sha1, Sha1Error};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// Directory under the root for blobs that are still being written
+const TMP_DIR: &str = "tmp";
+
+// Distinguishes the temporary files of concurrent puts within one process
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A content-addressable blob store rooted at a directory.
+#[derive(Clone, Debug)]
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    /// Opens the store rooted at `root`, creating the directory if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Store, Sha1Error> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join(TMP_DIR))?;
+        Ok(Store { root })
+    }
+
+    /// Returns the root directory of the store.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path where the blob with the given hash is stored, whether or not it exists.
+    pub fn path(&self, digest: &Digest) -> PathBuf {
+        let hex = digest.to_hex();
+        self.root.join(&hex[..2]).join(&hex[2..])
+    }
+
+    /// Returns whether the store has a blob with the given hash.
+    pub fn contains(&self, digest: &Digest) -> bool {
+        self.path(digest).is_file()
+    }
+
+    /// Stores a blob and returns its hash. Storing a blob that is already in the store does
+    /// nothing.
+    pub fn put<D: AsRef<[u8]>>(&self, data: D) -> Result<Digest, Sha1Error> {
+        let data = data.as_ref();
+        let digest = Digest::from(Sha1::digest(data));
+        if self.contains(&digest) {
+            return Ok(digest);
+        }
+
+        self.write_blob(|out| {
+            out.write_all(data)?;
+            Ok(digest)
+        })
+    }
+
+    /// Stores everything from a reader as a blob and returns its hash, without holding the
+    /// whole blob in memory.
+    pub fn put_reader<R: Read>(&self, reader: R) -> Result<Digest, Sha1Error> {
+        self.write_blob(|out| {
+            let (_, digest) = Sha1::copy_and_hash(reader, out)?;
+            Ok(digest)
+        })
+    }
+
+    /// Reads back the blob with the given hash, checking that its contents still have that
+    /// hash.
+    ///
+    /// Returns `Sha1Error::Mismatch` if the blob was corrupted, or an I/O error with
+    /// `io::ErrorKind::NotFound` if the store has no such blob.
+    pub fn get(&self, digest: &Digest) -> Result<Vec<u8>, Sha1Error> {
+        let data = fs::read(self.path(digest))?;
+
+        let actual = Sha1::digest(&data);
+        if actual != digest.words() {
+            return Err(Sha1Error::Mismatch {
+                expected: digest.words(),
+                actual,
+            });
+        }
+
+        Ok(data)
+    }
+
+    /// Removes every blob for which `keep` returns false, along with leftover temporary files of
+    /// puts that never finished. Returns the number of blobs removed.
+    ///
+    /// Must not run while other puts into the same store are in progress, as their temporary
+    /// files would be removed.
+    pub fn gc<F: FnMut(&Digest) -> bool>(&self, mut keep: F) -> Result<usize, Sha1Error> {
+        let mut removed = 0;
+
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let prefix = match name.to_str() {
+                Some(s) if s.len() == 2 && entry.file_type()?.is_dir() => s.to_owned(),
+                _ => continue,
+            };
+
+            for blob in fs::read_dir(entry.path())? {
+                let blob = blob?;
+                // Anything that isn't named like a blob was not put there by the store
+                let digest = match blob.file_name().to_str() {
+                    Some(rest) => match hex::decode(&(prefix.clone() + rest)) {
+                        Ok(hash) => Digest::from(hash),
+                        Err(_) => continue,
+                    },
+                    None => continue,
+                };
+
+                if !keep(&digest) {
+                    fs::remove_file(blob.path())?;
+                    removed += 1;
+                }
+            }
+
+            // Fails harmlessly if the directory still has blobs
+            let _ = fs::remove_dir(entry.path());
+        }
+
+        for tmp in fs::read_dir(self.root.join(TMP_DIR))? {
+            fs::remove_file(tmp?.path())?;
+        }
+
+        Ok(removed)
+    }
+
+    // Writes a blob to a temporary file with fill, which returns its hash, then moves it into
+    // place once it's completely on disk. The temporary file is removed if anything fails.
+    fn write_blob<F>(&self, fill: F) -> Result<Digest, Sha1Error>
+    where
+        F: FnOnce(&mut File) -> Result<Digest, Sha1Error>,
+    {
+        let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp = self
+            .root
+            .join(TMP_DIR)
+            .join(format!("{}-{}", process::id(), n));
+
+        let result = File::create(&tmp)
+            .map_err(Sha1Error::from)
+            .and_then(|mut out| {
+                let digest = fill(&mut out)?;
+                out.sync_all()?;
+
+                let path = self.path(&digest);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::rename(&tmp, path)?;
+                Ok(digest)
+            });
+
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        result
+    }
+}
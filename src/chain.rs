@@ -0,0 +1,65 @@
+//! Hash chains, where each link is the hash of the one before it, as used by one-time password
+//! schemes like S/KEY (RFC 1760) and by hash-based commitments.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::{chain, Sha1};
+//! // The end of the chain is published, earlier links are revealed one at a time
+//! let links = chain::hash_chain_links(b"secret seed", 100);
+//! assert!(Sha1::digest(sha1::Digest::from(links[98]).bytes()) == links[99]);
+//! assert!(chain::hash_chain(b"secret seed", 100) == links[99]);
+//! ```
+
+use crate::{Hash, Sha1};
+
+/// Returns SHA-1 applied `n` times: the hash of `seed`, then the hash of the 20 bytes of that
+/// hash, and so on.
+///
+/// Panics if `n` is 0.
+pub fn hash_chain<D: AsRef<[u8]>>(seed: D, n: usize) -> Hash {
+    assert!(n > 0, "hash chain length must not be 0");
+
+    let mut s = Sha1::new();
+    let mut hash = Sha1::digest(seed);
+    for _ in 1..n {
+        hash = digest_link(&mut s, &hash);
+    }
+
+    hash
+}
+
+/// Same as `hash_chain()`, but returns all `n` links in order. The last link is the result of
+/// `hash_chain()`.
+pub fn hash_chain_links<D: AsRef<[u8]>>(seed: D, n: usize) -> Vec<Hash> {
+    let mut links = Vec::with_capacity(n);
+    if n == 0 {
+        return links;
+    }
+
+    let mut s = Sha1::new();
+    links.push(Sha1::digest(seed));
+    for i in 1..n {
+        let link = digest_link(&mut s, &links[i - 1]);
+        links.push(link);
+    }
+
+    links
+}
+
+// Hashes the 20 bytes of a hash. They always fit in one chunk along with the padding, so the
+// chunk is filled in directly rather than going through update() and finish().
+fn digest_link(s: &mut Sha1, hash: &Hash) -> Hash {
+    s.reset();
+
+    for (i, word) in hash.iter().enumerate() {
+        s.chunk[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
+    }
+    s.chunk[20] = 0x80;
+    for b in &mut s.chunk[21..56] {
+        *b = 0;
+    }
+    s.chunk[56..64].copy_from_slice(&160u64.to_be_bytes());
+
+    s.process_chunk();
+    [s.h0, s.h1, s.h2, s.h3, s.h4]
+}
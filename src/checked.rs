@@ -0,0 +1,154 @@
+use crate::compress::compress_reference;
+use crate::{Hash, Sha1Error, INITIAL_STATE};
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// `Sha1` that computes every block twice, with the active backend and with a second portable
+/// implementation, and returns `Sha1Error::Corrupted` if the two ever disagree.
+///
+/// This is for long-running hashes whose result is stored as the data's checksum, where a bit
+/// flipped in memory or a faulty CPU during hashing would otherwise be baked into the checksum
+/// without anyone noticing. It is at least twice as slow as `Sha1`.
+///
+/// Once a mismatch is found, every later call returns the same error.
+///
+/// Example usage:
+/// ```
+/// # use sha1::{CheckedSha1, Sha1};
+/// let mut hasher = CheckedSha1::new();
+/// hasher.update(b"archived data").unwrap();
+/// assert_eq!(hasher.finish().unwrap(), Sha1::digest(b"archived data"));
+/// ```
+#[derive(Clone)]
+pub struct CheckedSha1 {
+    fast: Hash,
+    reference: Hash,
+    chunk: [u8; 64],
+    used: usize,
+    chunks: u64,
+    failed: Option<u64>,
+}
+
+impl Default for CheckedSha1 {
+    fn default() -> CheckedSha1 {
+        CheckedSha1::new()
+    }
+}
+
+impl CheckedSha1 {
+    /// Creates a new empty `CheckedSha1` struct.
+    pub const fn new() -> CheckedSha1 {
+        CheckedSha1 {
+            fast: INITIAL_STATE,
+            reference: INITIAL_STATE,
+            chunk: [0; 64],
+            used: 0,
+            chunks: 0,
+            failed: None,
+        }
+    }
+
+    /// Hashes `data` with both implementations, returning the hash or `Sha1Error::Corrupted`.
+    pub fn digest<D: AsRef<[u8]>>(data: D) -> Result<Hash, Sha1Error> {
+        let mut hasher = CheckedSha1::new();
+        hasher.update(data)?;
+        hasher.finish()
+    }
+
+    /// Adds data to the hash. Returns `Sha1Error::Corrupted` if the two implementations
+    /// disagree on any block completed so far.
+    pub fn update<D: AsRef<[u8]>>(&mut self, data: D) -> Result<(), Sha1Error> {
+        let mut data = data.as_ref();
+        self.check()?;
+
+        while !data.is_empty() {
+            let n = data.len().min(64 - self.used);
+            self.chunk[self.used..self.used + n].copy_from_slice(&data[..n]);
+            self.used += n;
+            data = &data[n..];
+
+            if self.used == 64 {
+                self.process_chunk()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of bytes added so far.
+    pub fn bytes_processed(&self) -> u64 {
+        self.chunks * 64 + self.used as u64
+    }
+
+    /// Finishes all work for the hash and returns the final result, which both implementations
+    /// agree on. Returns `Sha1Error::LengthOverflow` if more than 2^64 - 1 bits were added.
+    pub fn finish(mut self) -> Result<Hash, Sha1Error> {
+        self.check()?;
+        let bit_len = self
+            .bytes_processed()
+            .checked_mul(8)
+            .ok_or(Sha1Error::LengthOverflow)?;
+
+        // Pad the last chunk, spilling into one more chunk if the length doesn't fit
+        let used = self.used;
+        self.chunk[used] = 0x80;
+        self.chunk[used + 1..].fill(0);
+        if used >= 56 {
+            self.process_chunk()?;
+            self.chunk = [0; 64];
+        }
+        self.chunk[56..].copy_from_slice(&bit_len.to_be_bytes());
+        self.process_chunk()?;
+
+        Ok(self.fast)
+    }
+
+    /// Finishes the hash like `CheckedSha1::finish()`, and resets the `CheckedSha1` struct so
+    /// it can be used for the next hash.
+    pub fn finish_reset(&mut self) -> Result<Hash, Sha1Error> {
+        let hasher = self.clone();
+        self.reset();
+        hasher.finish()
+    }
+
+    /// Discards all data added so far, along with any mismatch found in it.
+    pub fn reset(&mut self) {
+        *self = CheckedSha1::new();
+    }
+
+    // Compresses the full chunk with both implementations and compares the results
+    fn process_chunk(&mut self) -> Result<(), Sha1Error> {
+        crate::compress(&mut self.fast, &self.chunk);
+        self.reference = compress_reference(self.reference, &self.chunk);
+
+        if self.fast != self.reference {
+            self.failed = Some(self.chunks * 64);
+        }
+        self.chunks += 1;
+        self.used = 0;
+        self.check()
+    }
+
+    fn check(&self) -> Result<(), Sha1Error> {
+        match self.failed {
+            Some(offset) => Err(Sha1Error::Corrupted { offset }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Write for CheckedSha1 {
+    /// Adds all data to the hash by calling `self.update(data)`. Mismatches are returned as
+    /// `io::ErrorKind::InvalidData` errors.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data)?;
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+// The SHA-1 compression function and its backends.
+
+use crate::backend;
+use crate::Hash;
+
+#[cfg(all(
+    feature = "asm",
+    target_arch = "x86_64",
+    target_feature = "sha",
+    target_feature = "sse4.1"
+))]
+use crate::asm;
+#[cfg(feature = "portable-simd")]
+use crate::simd;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use crate::wasm32;
+
+/// Hash value words that every hash starts from, before any data is processed.
+pub const INITIAL_STATE: Hash = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// Runs the SHA-1 compression function on one 64 byte block, updating the five hash value words
+/// in `state`.
+///
+/// This is the core that `Sha1` is built on, for constructions that handle padding and message
+/// length themselves. A standard hash starts from `INITIAL_STATE`, and its last block ends with
+/// the padding from `Sha1::padding()`.
+///
+/// Example usage:
+/// ```
+/// # use sha1::Sha1;
+/// // Hash an empty message by compressing only its padding
+/// let mut state = sha1::INITIAL_STATE;
+/// let mut block = [0; 64];
+/// block.copy_from_slice(&Sha1::padding(0));
+/// sha1::compress(&mut state, &block);
+/// assert_eq!(state, Sha1::digest(b""));
+/// ```
+pub fn compress(state: &mut Hash, block: &[u8; 64]) {
+    *state = match backend::active() {
+        #[cfg(all(
+            feature = "asm",
+            target_arch = "x86_64",
+            target_feature = "sha",
+            target_feature = "sse4.1"
+        ))]
+        backend::Backend::X86ShaAsm => asm::compress(*state, block),
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        backend::Backend::WasmSimd128 => compress_scheduled(*state, block, wasm32::schedule),
+        #[cfg(feature = "portable-simd")]
+        backend::Backend::PortableSimd => compress_scheduled(*state, block, simd::schedule),
+        _ => compress_portable::<false>(*state, block),
+    };
+}
+
+// One round, with the five working variables named in their order for this round. Instead of
+// moving every variable along, the next round names them in a different order.
+macro_rules! round {
+    ($a:ident, $b:ident, $c:ident, $d:ident, $e:ident, $f:ident, $k:expr, $w:expr) => {
+        // Using wrapping_add instead of + prevents overflow panic in debug mode
+        // but also produces equivalent code to + in release mode.
+        $e = leftrotate($a, 5)
+            .wrapping_add($f!($b, $c, $d))
+            .wrapping_add($e)
+            .wrapping_add($k)
+            .wrapping_add($w);
+        $b = leftrotate($b, 30);
+    };
+}
+
+// Five rounds starting at round $i, after which the variables are back in their usual order.
+macro_rules! round5 {
+    ($a:ident, $b:ident, $c:ident, $d:ident, $e:ident, $f:ident, $k:expr, $w:ident, $i:expr) => {
+        round!($a, $b, $c, $d, $e, $f, $k, $w!($i));
+        round!($e, $a, $b, $c, $d, $f, $k, $w!($i + 1));
+        round!($d, $e, $a, $b, $c, $f, $k, $w!($i + 2));
+        round!($c, $d, $e, $a, $b, $f, $k, $w!($i + 3));
+        round!($b, $c, $d, $e, $a, $f, $k, $w!($i + 4));
+    };
+}
+
+macro_rules! choose {
+    ($b:expr, $c:expr, $d:expr) => {
+        ($b & $c) | ((!$b) & $d)
+    };
+}
+
+macro_rules! parity {
+    ($b:expr, $c:expr, $d:expr) => {
+        $b ^ $c ^ $d
+    };
+}
+
+macro_rules! majority {
+    ($b:expr, $c:expr, $d:expr) => {
+        ($b & $c) | ($b & $d) | ($c & $d)
+    };
+}
+
+// Runs all 80 rounds, fully unrolled, on hash value words $h and evaluates to the new hash value
+// words. $w!(i) must give the schedule word for round i, and is used once per round, in order.
+macro_rules! rounds {
+    ($h:expr, $w:ident) => {{
+        let h: Hash = $h;
+
+        // Initialize hash value for this chunk
+        let mut a = h[0];
+        let mut b = h[1];
+        let mut c = h[2];
+        let mut d = h[3];
+        let mut e = h[4];
+
+        // Do some hashing...
+        round5!(a, b, c, d, e, choose, 0x5A827999, $w, 0);
+        round5!(a, b, c, d, e, choose, 0x5A827999, $w, 5);
+        round5!(a, b, c, d, e, choose, 0x5A827999, $w, 10);
+        round5!(a, b, c, d, e, choose, 0x5A827999, $w, 15);
+
+        round5!(a, b, c, d, e, parity, 0x6ED9EBA1, $w, 20);
+        round5!(a, b, c, d, e, parity, 0x6ED9EBA1, $w, 25);
+        round5!(a, b, c, d, e, parity, 0x6ED9EBA1, $w, 30);
+        round5!(a, b, c, d, e, parity, 0x6ED9EBA1, $w, 35);
+
+        round5!(a, b, c, d, e, majority, 0x8F1BBCDC, $w, 40);
+        round5!(a, b, c, d, e, majority, 0x8F1BBCDC, $w, 45);
+        round5!(a, b, c, d, e, majority, 0x8F1BBCDC, $w, 50);
+        round5!(a, b, c, d, e, majority, 0x8F1BBCDC, $w, 55);
+
+        round5!(a, b, c, d, e, parity, 0xCA62C1D6, $w, 60);
+        round5!(a, b, c, d, e, parity, 0xCA62C1D6, $w, 65);
+        round5!(a, b, c, d, e, parity, 0xCA62C1D6, $w, 70);
+        round5!(a, b, c, d, e, parity, 0xCA62C1D6, $w, 75);
+
+        [
+            h[0].wrapping_add(a),
+            h[1].wrapping_add(b),
+            h[2].wrapping_add(c),
+            h[3].wrapping_add(d),
+            h[4].wrapping_add(e),
+        ]
+    }};
+}
+
+// Processes one chunk and returns the new hash value words. SHA-0 is the same without the
+// rotate in the message schedule.
+//
+// Only the last 16 schedule words are kept, in a window that each round overwrites with the
+// word it uses. This is a const fn so that Sha1::digest_const() can use it, which is why it
+// uses a while loop instead of a for loop.
+pub(crate) const fn compress_chunk<const SHA0: bool>(h: Hash, chunk: &[u8; 64]) -> Hash {
+    let mut w = [0u32; 16];
+
+    // Fill the window with data from the chunk
+    let mut i = 0;
+    while i < 16 {
+        let j = i * 4;
+        w[i] = u32::from_be_bytes([chunk[j], chunk[j + 1], chunk[j + 2], chunk[j + 3]]);
+        i += 1;
+    }
+
+    // Word i of the schedule is made from words i - 3, i - 8, i - 14, and i - 16, which are
+    // still in the window
+    macro_rules! word {
+        ($i:expr) => {{
+            if $i >= 16 {
+                let x = w[($i + 13) & 15] ^ w[($i + 8) & 15] ^ w[($i + 2) & 15] ^ w[$i & 15];
+                w[$i & 15] = if SHA0 { x } else { leftrotate(x, 1) };
+            }
+            w[$i & 15]
+        }};
+    }
+
+    rounds!(h, word)
+}
+
+// The portable backend: compress_chunk() normally, or compress_small() with the small feature.
+#[inline(always)]
+pub(crate) fn compress_portable<const SHA0: bool>(h: Hash, chunk: &[u8; 64]) -> Hash {
+    #[cfg(feature = "small")]
+    {
+        compress_small::<SHA0>(h, chunk)
+    }
+
+    #[cfg(not(feature = "small"))]
+    {
+        compress_chunk::<SHA0>(h, chunk)
+    }
+}
+
+// The portable implementation that compress_portable() doesn't use, so that CheckedSha1 always
+// has a second implementation to check every backend against.
+pub(crate) fn compress_reference(h: Hash, chunk: &[u8; 64]) -> Hash {
+    #[cfg(feature = "small")]
+    {
+        compress_chunk::<false>(h, chunk)
+    }
+
+    #[cfg(not(feature = "small"))]
+    {
+        compress_small::<false>(h, chunk)
+    }
+}
+
+// Same as compress_chunk(), but with the rounds in a loop instead of unrolled, for the small
+// feature and compress_reference(). Compiles to a fraction of the code, but is slower.
+#[inline(never)]
+pub(crate) fn compress_small<const SHA0: bool>(h: Hash, chunk: &[u8; 64]) -> Hash {
+    let mut w = [0u32; 16];
+    for (word, bytes) in w.iter_mut().zip(chunk.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = h;
+    for i in 0..80 {
+        if i >= 16 {
+            let x = w[(i + 13) & 15] ^ w[(i + 8) & 15] ^ w[(i + 2) & 15] ^ w[i & 15];
+            w[i & 15] = if SHA0 { x } else { x.rotate_left(1) };
+        }
+
+        let (f, k) = match i / 20 {
+            0 => ((b & c) | (!b & d), 0x5A827999),
+            1 => (b ^ c ^ d, 0x6ED9EBA1),
+            2 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+            _ => (b ^ c ^ d, 0xCA62C1D6),
+        };
+
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp.wrapping_add(w[i & 15]);
+    }
+
+    [
+        h[0].wrapping_add(a),
+        h[1].wrapping_add(b),
+        h[2].wrapping_add(c),
+        h[3].wrapping_add(d),
+        h[4].wrapping_add(e),
+    ]
+}
+
+// Same as compress_chunk::<false>(), but with the whole 80 word schedule computed up front by
+// one of the SIMD schedule functions.
+#[cfg(any(
+    all(target_arch = "wasm32", target_feature = "simd128"),
+    feature = "portable-simd"
+))]
+#[inline(always)]
+pub(crate) fn compress_scheduled(
+    h: Hash,
+    chunk: &[u8; 64],
+    schedule: fn(&[u8; 64], &mut [u32; 80]),
+) -> Hash {
+    let mut w = [0u32; 80];
+    schedule(chunk, &mut w);
+
+    macro_rules! word {
+        ($i:expr) => {
+            w[$i]
+        };
+    }
+
+    rounds!(h, word)
+}
+
+const fn leftrotate(word: u32, bits: u8) -> u32 {
+    (word << bits) | (word >> (32 - bits))
+}
@@ -0,0 +1,242 @@
+use crate::{hash_to_bytes, hex, Hash, Sha1Error};
+use core::borrow::Borrow;
+use core::convert::{TryFrom, TryInto};
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::{base32, base64};
+
+/// Byte representation of a hash result, with conversions to common text encodings.
+///
+/// Example usage:
+/// ```
+/// # use sha1::{Digest, Sha1};
+/// let digest = Digest::from(Sha1::digest(b"Hello, world"));
+/// assert_eq!(digest.to_string(), "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8");
+/// assert_eq!(digest.to_base64(), "4CqhsQbVx8apje8rEwBdW4T9jcg=");
+/// ```
+///
+/// Digests order and hash the same as their bytes, so they can be used as map keys and looked up
+/// by byte slices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Digest([u8; 20]);
+
+impl Digest {
+    /// Creates a digest from the 20 bytes of a hash.
+    pub const fn from_bytes(bytes: [u8; 20]) -> Digest {
+        Digest(bytes)
+    }
+
+    /// Creates a digest from five hash words, each stored in big-endian order.
+    pub fn from_words(hash: Hash) -> Digest {
+        Digest(hash_to_bytes(&hash))
+    }
+
+    /// Returns the 20 bytes of the hash.
+    pub fn bytes(&self) -> [u8; 20] {
+        self.0
+    }
+
+    /// Returns the hash as five big-endian words.
+    pub fn words(&self) -> Hash {
+        let word = |i: usize| u32::from_be_bytes(self.0[i * 4..(i + 1) * 4].try_into().unwrap());
+        [word(0), word(1), word(2), word(3), word(4)]
+    }
+
+    /// Formats the hash as 40 lowercase hex digits. Same as `to_string()`.
+    #[cfg(feature = "std")]
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.words())
+    }
+
+    /// Formats the hash as colon-separated pairs of uppercase hex digits (`AB:CD:...`), as
+    /// certificate and SSH key fingerprints are traditionally displayed.
+    #[cfg(feature = "std")]
+    pub fn to_fingerprint(&self) -> String {
+        let pairs: Vec<String> = self.0.iter().map(|b| format!("{:02X}", b)).collect();
+        pairs.join(":")
+    }
+
+    /// Parses a hash formatted by `Digest::to_fingerprint()`. Hex digits of either case are
+    /// accepted.
+    ///
+    /// Returns `Sha1Error::InvalidHex` if the string is not exactly 20 colon-separated pairs of
+    /// hex digits.
+    pub fn from_fingerprint(s: &str) -> Result<Digest, Sha1Error> {
+        let mut bytes = [0; 20];
+        let mut pairs = s.split(':');
+
+        for b in bytes.iter_mut() {
+            let pair = pairs.next().ok_or(Sha1Error::InvalidHex)?;
+            if pair.len() != 2 || !pair.bytes().all(|c| c.is_ascii_hexdigit()) {
+                return Err(Sha1Error::InvalidHex);
+            }
+            *b = u8::from_str_radix(pair, 16).unwrap();
+        }
+
+        match pairs.next() {
+            Some(_) => Err(Sha1Error::InvalidHex),
+            None => Ok(Digest(bytes)),
+        }
+    }
+
+    /// Formats the hash as base32 with the standard alphabet (RFC 4648 section 6), as used by
+    /// magnet links. A 20 byte hash is exactly 32 characters, so there is never any padding.
+    #[cfg(feature = "std")]
+    pub fn to_base32(&self) -> String {
+        base32::encode(&self.0, false)
+    }
+
+    /// Formats the hash as padded base64 with the standard alphabet (RFC 4648 section 4), as
+    /// used by WebSocket handshakes and XML signatures.
+    #[cfg(feature = "std")]
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.0)
+    }
+
+    /// Formats the hash as unpadded base64 with the URL and filename safe alphabet (RFC 4648
+    /// section 5).
+    #[cfg(feature = "std")]
+    pub fn to_base64_url(&self) -> String {
+        base64::encode_with(&self.0, base64::URL_SAFE, false)
+    }
+
+    /// Returns the leftmost 96 bits of the hash, as used by HMAC-SHA-1-96 in IPsec (RFC 2404)
+    /// and SNMPv3 (RFC 3414).
+    pub fn truncate96(&self) -> Digest96 {
+        Digest96(self.0[..12].try_into().unwrap())
+    }
+
+    /// Returns the first 8 bytes of the hash as a big-endian integer. Since hash output is
+    /// uniformly distributed, so is this integer.
+    pub fn to_u64(&self) -> u64 {
+        u64::from_be_bytes(self.0[..8].try_into().unwrap())
+    }
+
+    /// Maps the hash to one of `n` buckets, such as the shard that a record keyed by this hash
+    /// belongs to, returning an index less than `n`.
+    ///
+    /// The index is the high half of `to_u64() * n`, rather than `to_u64() % n`, so that every
+    /// bucket covers a contiguous range of the same size within one: no bucket is more likely
+    /// than another by more than 1 in 2^64. Lower indices cover lower hashes.
+    ///
+    /// Panics if `n` is 0.
+    pub fn bucket(&self, n: u64) -> u64 {
+        assert!(n > 0, "number of buckets must not be 0");
+        ((self.to_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+/// The leftmost 96 bits of a hash, produced by `Digest::truncate96()`.
+///
+/// Kept as a separate type so that truncated and full length tags can't be mixed up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Digest96([u8; 12]);
+
+impl Digest96 {
+    /// Returns the 12 bytes of the truncated hash.
+    pub fn bytes(&self) -> [u8; 12] {
+        self.0
+    }
+
+    /// Formats the truncated hash as 24 lowercase hex digits. Same as `to_string()`.
+    #[cfg(feature = "std")]
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl From<Hash> for Digest {
+    /// Converts hash words to bytes, in big-endian order.
+    fn from(hash: Hash) -> Digest {
+        Digest::from_words(hash)
+    }
+}
+
+impl From<[u8; 20]> for Digest {
+    /// Wraps the bytes of a hash.
+    fn from(bytes: [u8; 20]) -> Digest {
+        Digest(bytes)
+    }
+}
+
+impl From<Digest> for [u8; 20] {
+    /// Returns the bytes of a hash.
+    fn from(digest: Digest) -> [u8; 20] {
+        digest.0
+    }
+}
+
+impl TryFrom<&[u8]> for Digest {
+    type Error = Sha1Error;
+
+    /// Copies the bytes of a hash. Returns `Sha1Error::InvalidLength` if the slice is not exactly
+    /// 20 bytes long.
+    fn try_from(bytes: &[u8]) -> Result<Digest, Sha1Error> {
+        bytes
+            .try_into()
+            .map(Digest)
+            .map_err(|_| Sha1Error::InvalidLength)
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<[u8]> for Digest {
+    fn borrow(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Digest> for Hash {
+    /// Converts hash bytes to words, in big-endian order.
+    fn from(digest: Digest) -> Hash {
+        digest.words()
+    }
+}
+
+impl fmt::Display for Digest {
+    /// Formats the hash as 40 lowercase hex digits.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl fmt::Display for Digest96 {
+    /// Formats the truncated hash as 24 lowercase hex digits.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl PartialEq<str> for Digest {
+    /// Compares against 40 hex digits of either case. Anything else is not equal.
+    fn eq(&self, other: &str) -> bool {
+        hex::decode(other).ok() == Some(self.words())
+    }
+}
+
+impl PartialEq<&str> for Digest {
+    /// Compares against 40 hex digits of either case. Anything else is not equal.
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
+impl PartialEq<Digest> for str {
+    /// Compares against 40 hex digits of either case. Anything else is not equal.
+    fn eq(&self, other: &Digest) -> bool {
+        *other == *self
+    }
+}
+
+impl PartialEq<Digest> for &str {
+    /// Compares against 40 hex digits of either case. Anything else is not equal.
+    fn eq(&self, other: &Digest) -> bool {
+        *other == **self
+    }
+}
@@ -0,0 +1,228 @@
+//! Recursive hashing of every file in a directory tree.
+//!
+//! With the `rayon` feature enabled, files are hashed in parallel.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::dir::{hash_dir, DirOptions, Symlinks};
+//! # use std::ffi::OsStr;
+//! # fn f() -> std::io::Result<()> {
+//! // Hash every file except those in .git directories, following symlinks
+//! let options = DirOptions::new()
+//!     .symlinks(Symlinks::Follow)
+//!     .exclude(|path| path.file_name() == Some(OsStr::new(".git")));
+//!
+//! for (path, hash) in hash_dir("foo", &options)? {
+//!     println!("{}  {}", sha1::hex::encode(&hash), path.display());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Hash, Sha1, Sha1Error};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+type PathFilter = Box<dyn Fn(&Path) -> bool + Send + Sync>;
+
+/// How symbolic links are treated while walking a directory tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symlinks {
+    /// Ignore symbolic links entirely.
+    Skip,
+    /// Hash the targets of symbolic links to files, and walk symbolic links to directories.
+    /// Directories that were already walked are not walked again, so link cycles are safe.
+    Follow,
+}
+
+/// Options for `hash_dir`.
+pub struct DirOptions {
+    symlinks: Symlinks,
+    include: Vec<PathFilter>,
+    exclude: Vec<PathFilter>,
+}
+
+impl DirOptions {
+    /// Creates options that hash every file and skip symbolic links.
+    pub fn new() -> DirOptions {
+        DirOptions {
+            symlinks: Symlinks::Skip,
+            include: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Sets how symbolic links are treated. Defaults to `Symlinks::Skip`.
+    pub fn symlinks(mut self, symlinks: Symlinks) -> DirOptions {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Only hashes files for which `filter` returns true. If given multiple times, files must
+    /// pass every filter.
+    pub fn include<F>(mut self, filter: F) -> DirOptions
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        self.include.push(Box::new(filter));
+        self
+    }
+
+    /// Skips files and directories for which `filter` returns true. Excluded directories are not
+    /// walked at all.
+    pub fn exclude<F>(mut self, filter: F) -> DirOptions
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        self.exclude.push(Box::new(filter));
+        self
+    }
+
+    fn excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|f| f(path))
+    }
+
+    fn included(&self, path: &Path) -> bool {
+        self.include.iter().all(|f| f(path))
+    }
+}
+
+impl Default for DirOptions {
+    /// Equivalent to `DirOptions::new()`.
+    fn default() -> DirOptions {
+        DirOptions::new()
+    }
+}
+
+/// Hashes every file in the directory tree at `path` and returns each file's path along with its
+/// hash, sorted by path. The returned paths start with `path`.
+///
+/// Stops at the first I/O error.
+pub fn hash_dir<P>(path: P, options: &DirOptions) -> Result<Vec<(PathBuf, Hash)>, Sha1Error>
+where
+    P: AsRef<Path>,
+{
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    walk(path.as_ref(), options, &mut visited, &mut files)?;
+    files.sort();
+
+    #[cfg(feature = "rayon")]
+    let files = files.into_par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let files = files.into_iter();
+
+    files
+        .map(|path| Sha1::digest_path(&path).map(|(hash, _)| (path, hash)))
+        .collect()
+}
+
+/// Finds files with identical contents in the directory tree at `path`. See
+/// [`find_duplicate_files`](fn.find_duplicate_files.html).
+pub fn find_duplicates<P>(path: P, options: &DirOptions) -> Result<Vec<Vec<PathBuf>>, Sha1Error>
+where
+    P: AsRef<Path>,
+{
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    walk(path.as_ref(), options, &mut visited, &mut files)?;
+    find_duplicate_files(files)
+}
+
+/// Groups files with identical contents. Returns every group of two or more files with the same
+/// contents, each sorted by path, with the groups sorted by their first path.
+///
+/// Files are first grouped by size, so only files that share a size with another are hashed.
+///
+/// Stops at the first I/O error.
+pub fn find_duplicate_files<I>(paths: I) -> Result<Vec<Vec<PathBuf>>, Sha1Error>
+where
+    I: IntoIterator,
+    I::Item: Into<PathBuf>,
+{
+    let mut paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        let len = fs::metadata(&path)?.len();
+        by_size.entry(len).or_default().push(path);
+    }
+
+    let candidates: Vec<PathBuf> = by_size
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+
+    #[cfg(feature = "rayon")]
+    let candidates = candidates.into_par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let candidates = candidates.into_iter();
+
+    let hashed: Vec<(PathBuf, Hash)> = candidates
+        .map(|path| Sha1::digest_path(&path).map(|(hash, _)| (path, hash)))
+        .collect::<Result<_, _>>()?;
+
+    // Files of different sizes can't have the same hash, so grouping by hash alone is enough
+    let mut by_hash: HashMap<Hash, Vec<PathBuf>> = HashMap::new();
+    for (path, hash) in hashed {
+        by_hash.entry(hash).or_default().push(path);
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = by_hash
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort();
+
+    Ok(groups)
+}
+
+// Collects the paths of every file to hash in the directory tree at dir.
+pub(crate) fn walk(
+    dir: &Path,
+    options: &DirOptions,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    // Only matters when following symlinks, but is cheap enough to always do
+    if !visited.insert(dir.canonicalize()?) {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let mut file_type = entry.file_type()?;
+
+        if options.excluded(&path) {
+            continue;
+        }
+
+        if file_type.is_symlink() {
+            match options.symlinks {
+                Symlinks::Skip => continue,
+                Symlinks::Follow => file_type = fs::metadata(&path)?.file_type(),
+            }
+        }
+
+        if file_type.is_dir() {
+            walk(&path, options, visited, files)?;
+        } else if file_type.is_file() && options.included(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! DKIM body hashes for `rsa-sha1` signatures (RFC 6376 section 3.7).
+//!
+//! The body is canonicalized and hashed, and the `bh=` tag of a signature holds the base64
+//! encoded hash. Lines may end in either CRLF or a bare LF, and are hashed as ending in CRLF.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::dkim::{self, Canonicalization};
+//! let body = b"Hello,  world \r\n\r\n";
+//! assert_eq!(dkim::body_hash(body, Canonicalization::Relaxed), "sy/+kkLM3pjW4Z7UydvAU9ShgVU=");
+//! ```
+
+use crate::{base64, hash_to_bytes, Sha1};
+
+/// Body canonicalization algorithm, from the `c=` tag of a signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Canonicalization {
+    /// Trailing empty lines are removed, and nothing else is changed (RFC 6376 section 3.4.3).
+    Simple,
+    /// Whitespace at the end of lines is removed, other runs of spaces and tabs are reduced to a
+    /// single space, and trailing empty lines are removed (RFC 6376 section 3.4.4).
+    Relaxed,
+}
+
+/// Returns the canonicalized body, as it is hashed.
+pub fn canonicalize_body(body: &[u8], canonicalization: Canonicalization) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 2);
+    canonicalize(body, canonicalization, |part| out.extend_from_slice(part));
+    out
+}
+
+/// Returns the base64 encoded SHA-1 of the canonicalized body, for comparing to the `bh=` tag.
+pub fn body_hash(body: &[u8], canonicalization: Canonicalization) -> String {
+    let mut s = Sha1::new();
+    canonicalize(body, canonicalization, |part| s.update(part));
+    base64::encode(&hash_to_bytes(&s.finish()))
+}
+
+// Passes the canonicalized body to output in parts, without building it in memory.
+fn canonicalize<F>(body: &[u8], canonicalization: Canonicalization, mut output: F)
+where
+    F: FnMut(&[u8]),
+{
+    // A line ending at the very end doesn't start another line
+    let body = body.strip_suffix(b"\n").unwrap_or(body);
+    let lines = (!body.is_empty()).then(|| body.split(|&b| b == b'\n'));
+
+    let mut relaxed = Vec::new();
+    let mut empty_lines = 0;
+    let mut any_lines = false;
+
+    for line in lines.into_iter().flatten() {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let line = match canonicalization {
+            Canonicalization::Simple => line,
+            Canonicalization::Relaxed => {
+                relaxed.clear();
+                relax_line(line, &mut relaxed);
+                &relaxed
+            }
+        };
+
+        // Empty lines are only output once a line with content follows them
+        if line.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+
+        for _ in 0..empty_lines {
+            output(b"\r\n");
+        }
+        empty_lines = 0;
+        any_lines = true;
+
+        output(line);
+        output(b"\r\n");
+    }
+
+    // A simple body with no content is a single line ending, and a relaxed one is empty
+    if !any_lines && canonicalization == Canonicalization::Simple {
+        output(b"\r\n");
+    }
+}
+
+// Removes whitespace at the end of a line and reduces other runs of it to one space.
+fn relax_line(line: &[u8], out: &mut Vec<u8>) {
+    let is_wsp = |b: &u8| *b == b' ' || *b == b'\t';
+    let mut in_wsp = false;
+
+    for &b in line {
+        if is_wsp(&b) {
+            in_wsp = true;
+            continue;
+        }
+        if in_wsp {
+            out.push(b' ');
+            in_wsp = false;
+        }
+        out.push(b);
+    }
+}
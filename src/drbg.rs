@@ -0,0 +1,184 @@
+//! Hash_DRBG, the hash-based deterministic random bit generator of NIST SP 800-90A section 10.1.1,
+//! instantiated with SHA-1.
+//!
+//! The same entropy, nonce, personalization string and calls produce the same output, so results
+//! can be checked against the NIST test vectors or another implementation, such as an HSM's.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::drbg::HashDrbg;
+//! # fn f(entropy: &[u8], nonce: &[u8]) -> Result<(), sha1::Sha1Error> {
+//! let mut drbg = HashDrbg::new(entropy, nonce, b"my application");
+//! let mut key = [0; 16];
+//! drbg.generate(&mut key, b"")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{hash_to_bytes, Sha1, Sha1Error};
+
+/// Length of the internal values `V` and `C` in bytes, `seedlen` in SP 800-90A table 2.
+pub const SEED_LEN: usize = 55;
+
+/// Most bytes that one call to `HashDrbg::generate()` can produce (2^19 bits).
+pub const MAX_REQUEST_LEN: usize = 1 << 16;
+
+/// Most calls to `HashDrbg::generate()` allowed between reseeds (2^48), and the default reseed
+/// interval.
+pub const MAX_RESEED_INTERVAL: u64 = 1 << 48;
+
+/// Least entropy input accepted, for SHA-1's security strength of 128 bits.
+pub const MIN_ENTROPY_LEN: usize = 16;
+
+/// SHA-1 Hash_DRBG state.
+#[derive(Clone)]
+pub struct HashDrbg {
+    v: [u8; SEED_LEN],
+    c: [u8; SEED_LEN],
+    reseed_counter: u64,
+    reseed_interval: u64,
+}
+
+impl HashDrbg {
+    /// Instantiates the generator (SP 800-90A section 10.1.1.2) from entropy input, a nonce, and
+    /// a personalization string, which may be empty.
+    ///
+    /// Panics if `entropy` is shorter than `MIN_ENTROPY_LEN`.
+    pub fn new(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> HashDrbg {
+        assert!(
+            entropy.len() >= MIN_ENTROPY_LEN,
+            "entropy input is too short"
+        );
+
+        let mut drbg = HashDrbg {
+            v: [0; SEED_LEN],
+            c: [0; SEED_LEN],
+            reseed_counter: 0,
+            reseed_interval: MAX_RESEED_INTERVAL,
+        };
+        drbg.seed(&[entropy, nonce, personalization]);
+        drbg
+    }
+
+    /// Sets how many calls to `HashDrbg::generate()` are allowed before the generator must be
+    /// reseeded. Defaults to `MAX_RESEED_INTERVAL`.
+    ///
+    /// Panics if `interval` is 0 or more than `MAX_RESEED_INTERVAL`.
+    pub fn set_reseed_interval(&mut self, interval: u64) {
+        assert!(
+            interval > 0 && interval <= MAX_RESEED_INTERVAL,
+            "reseed interval must be between 1 and 2^48"
+        );
+        self.reseed_interval = interval;
+    }
+
+    /// Returns how many more calls to `HashDrbg::generate()` are allowed before the generator
+    /// must be reseeded.
+    pub fn remaining_before_reseed(&self) -> u64 {
+        (self.reseed_interval + 1).saturating_sub(self.reseed_counter)
+    }
+
+    /// Reseeds the generator (SP 800-90A section 10.1.1.3) with new entropy input and optional
+    /// additional input.
+    ///
+    /// Panics if `entropy` is shorter than `MIN_ENTROPY_LEN`.
+    pub fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        assert!(
+            entropy.len() >= MIN_ENTROPY_LEN,
+            "entropy input is too short"
+        );
+
+        let v = self.v;
+        self.seed(&[&[0x01], &v, entropy, additional]);
+    }
+
+    /// Fills `out` with pseudorandom bytes (SP 800-90A section 10.1.1.4), mixing in optional
+    /// additional input first.
+    ///
+    /// Returns `Sha1Error::ReseedRequired` without producing anything once the reseed interval
+    /// has passed.
+    ///
+    /// Panics if `out` is longer than `MAX_REQUEST_LEN`.
+    pub fn generate(&mut self, out: &mut [u8], additional: &[u8]) -> Result<(), Sha1Error> {
+        assert!(
+            out.len() <= MAX_REQUEST_LEN,
+            "too many bytes requested at once"
+        );
+        if self.reseed_counter > self.reseed_interval {
+            return Err(Sha1Error::ReseedRequired);
+        }
+
+        if !additional.is_empty() {
+            let w = hash(&[&[0x02], &self.v, additional]);
+            add(&mut self.v, &w);
+        }
+
+        // Hashgen: hash successive values of V to produce the output
+        let mut data = self.v;
+        for block in out.chunks_mut(20) {
+            block.copy_from_slice(&hash(&[&data])[..block.len()]);
+            add(&mut data, &[1]);
+        }
+
+        let h = hash(&[&[0x03], &self.v]);
+        let c = self.c;
+        add(&mut self.v, &h);
+        add(&mut self.v, &c);
+        add(&mut self.v, &self.reseed_counter.to_be_bytes());
+        self.reseed_counter += 1;
+
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut data);
+
+        Ok(())
+    }
+
+    // Derives V and C from seed material, shared by instantiating and reseeding
+    fn seed(&mut self, material: &[&[u8]]) {
+        self.v = hash_df(material);
+        self.c = hash_df(&[&[0x00], &self.v]);
+        self.reseed_counter = 1;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for HashDrbg {
+    /// Zeroes the internal state.
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.v);
+        zeroize::Zeroize::zeroize(&mut self.c);
+    }
+}
+
+// Hash of the concatenated parts
+fn hash(parts: &[&[u8]]) -> [u8; 20] {
+    hash_to_bytes(&Sha1::digest_parts(parts))
+}
+
+// Hash_df (SP 800-90A section 10.3.1), deriving SEED_LEN bytes from the concatenated parts
+fn hash_df(parts: &[&[u8]]) -> [u8; SEED_LEN] {
+    let bits = (SEED_LEN as u32 * 8).to_be_bytes();
+    let mut out = [0; SEED_LEN];
+
+    for (counter, block) in (1u8..).zip(out.chunks_mut(20)) {
+        let mut s = Sha1::new();
+        s.update([counter]);
+        s.update(bits);
+        s.update_iter(parts);
+        block.copy_from_slice(&hash_to_bytes(&s.finish())[..block.len()]);
+    }
+
+    out
+}
+
+// Adds a big-endian number to V, modulo 2^(8 * SEED_LEN)
+fn add(v: &mut [u8; SEED_LEN], n: &[u8]) {
+    let mut carry = 0;
+    let mut n = n.iter().rev();
+
+    for byte in v.iter_mut().rev() {
+        let sum = *byte as u16 + *n.next().unwrap_or(&0) as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{Digest, Hash};
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::Sha1;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::io;
+
+/// Error type for the fallible functions of this crate.
+///
+/// Some variants only exist with certain features enabled, so matches must have a wildcard arm
+/// to keep compiling when another crate in the build enables one of them.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Sha1Error {
+    /// Reading or writing failed.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// More than 2^64 - 1 bits were hashed, which SHA-1 does not support.
+    LengthOverflow,
+    /// A string was not exactly 40 hex digits.
+    InvalidHex,
+    /// A byte slice was not exactly 20 bytes long.
+    InvalidLength,
+    /// A state passed to `Sha1::import_state()` was not produced by `Sha1::export_state()`.
+    InvalidState,
+    /// Data did not have the hash it was expected to have.
+    Mismatch { expected: Hash, actual: Hash },
+    /// OpenSSL's libcrypto failed, with the given code from its error queue, or 0 if it didn't
+    /// give one. Only exists with the `openssl` feature, which `#[non_exhaustive]` keeps from
+    /// breaking matches in crates that don't enable it.
+    #[cfg(feature = "openssl")]
+    OpenSsl(u64),
+    /// A `drbg::HashDrbg` must be reseeded before it generates more output.
+    ReseedRequired,
+    /// The two implementations used by `CheckedSha1` computed different results for the block
+    /// starting at byte `offset`.
+    Corrupted { offset: u64 },
+    /// Hashing was cancelled with the flag set by `FileHasher::cancel_flag()`. Holds the hash
+    /// of everything read before then, which can be continued later. Only exists with the
+    /// `std` feature, which `#[non_exhaustive]` keeps from breaking matches in crates that don't
+    /// enable it.
+    #[cfg(feature = "std")]
+    Cancelled(Box<Sha1>),
+}
+
+/// Error returned by `Sha1::self_test()` when the active backend hashes a known-answer test
+/// vector wrong.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfTestError {
+    /// Name of the vector in RFC 3174, from `"TEST1"` to `"TEST4"`.
+    pub vector: &'static str,
+    pub expected: Hash,
+    pub actual: Hash,
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "self-test {} failed: expected {}, got {}",
+            self.vector,
+            Digest::from(self.expected),
+            Digest::from(self.actual)
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for SelfTestError {}
+
+impl fmt::Display for Sha1Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Sha1Error::Io(e) => e.fmt(f),
+            Sha1Error::LengthOverflow => f.write_str("message is longer than 2^64 - 1 bits"),
+            Sha1Error::InvalidHex => f.write_str("hash is not 40 hex digits"),
+            Sha1Error::InvalidLength => f.write_str("hash is not 20 bytes"),
+            Sha1Error::InvalidState => f.write_str("invalid exported hash state"),
+            Sha1Error::Mismatch { expected, actual } => write!(
+                f,
+                "hash mismatch: expected {}, got {}",
+                Digest::from(*expected),
+                Digest::from(*actual)
+            ),
+            #[cfg(feature = "openssl")]
+            Sha1Error::OpenSsl(code) => write!(f, "OpenSSL error {:#x}", code),
+            Sha1Error::ReseedRequired => f.write_str("DRBG must be reseeded"),
+            Sha1Error::Corrupted { offset } => {
+                write!(f, "hash computation disagreed at byte {}", offset)
+            }
+            #[cfg(feature = "std")]
+            Sha1Error::Cancelled(s) => write!(f, "cancelled after {} bytes", s.bytes_processed()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for Sha1Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Sha1Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Sha1Error {
+    /// Compares errors. I/O errors are compared only by their kind.
+    fn eq(&self, other: &Sha1Error) -> bool {
+        match (self, other) {
+            #[cfg(feature = "std")]
+            (Sha1Error::Io(a), Sha1Error::Io(b)) => a.kind() == b.kind(),
+            (Sha1Error::LengthOverflow, Sha1Error::LengthOverflow)
+            | (Sha1Error::InvalidHex, Sha1Error::InvalidHex)
+            | (Sha1Error::InvalidLength, Sha1Error::InvalidLength)
+            | (Sha1Error::InvalidState, Sha1Error::InvalidState)
+            | (Sha1Error::ReseedRequired, Sha1Error::ReseedRequired) => true,
+            (
+                Sha1Error::Mismatch { expected, actual },
+                Sha1Error::Mismatch {
+                    expected: other_expected,
+                    actual: other_actual,
+                },
+            ) => expected == other_expected && actual == other_actual,
+            (Sha1Error::Corrupted { offset }, Sha1Error::Corrupted { offset: other }) => {
+                offset == other
+            }
+            #[cfg(feature = "openssl")]
+            (Sha1Error::OpenSsl(a), Sha1Error::OpenSsl(b)) => a == b,
+            #[cfg(feature = "std")]
+            (Sha1Error::Cancelled(a), Sha1Error::Cancelled(b)) => {
+                a.export_state() == b.export_state()
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Sha1Error {
+    fn from(e: io::Error) -> Sha1Error {
+        Sha1Error::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Sha1Error> for io::Error {
+    /// Unwraps I/O errors, and wraps any other error as `io::ErrorKind::InvalidData`.
+    fn from(e: Sha1Error) -> io::Error {
+        match e {
+            Sha1Error::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
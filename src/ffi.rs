@@ -0,0 +1,65 @@
+//! C interface, enabled by the `ffi` feature. The matching header is `include/sha1.h`, which is
+//! generated from this file by cbindgen with `cbindgen.toml`.
+//!
+//! `cargo build --release -p sha1-ffi` builds `libsha1_ffi` as both a shared and a static
+//! library to link against.
+
+use crate::{hash_to_bytes, Sha1};
+use std::slice;
+
+/// Creates a new hash context. It must be passed to `sha1_finish()` or `sha1_free()` to be
+/// freed.
+#[no_mangle]
+pub extern "C" fn sha1_new() -> *mut Sha1 {
+    Box::into_raw(Box::new(Sha1::new()))
+}
+
+/// Adds `len` bytes from `data` to the hash.
+///
+/// # Safety
+/// `ctx` must come from `sha1_new()` and not be freed yet. `data` must point to `len` readable
+/// bytes, or may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn sha1_update(ctx: *mut Sha1, data: *const u8, len: usize) {
+    if len > 0 {
+        (*ctx).update(slice::from_raw_parts(data, len));
+    }
+}
+
+/// Writes the 20 byte hash to `out` and frees the context.
+///
+/// # Safety
+/// `ctx` must come from `sha1_new()` and not be freed yet. `out` must point to 20 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sha1_finish(ctx: *mut Sha1, out: *mut u8) {
+    let hash = Box::from_raw(ctx).finish();
+    slice::from_raw_parts_mut(out, 20).copy_from_slice(&hash_to_bytes(&hash));
+}
+
+/// Frees a context without finishing it. Does nothing if `ctx` is null.
+///
+/// # Safety
+/// `ctx` must be null, or come from `sha1_new()` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn sha1_free(ctx: *mut Sha1) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// Hashes `len` bytes from `data` and writes the 20 byte hash to `out`.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes, or may be null if `len` is 0. `out` must point to
+/// 20 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sha1_oneshot(data: *const u8, len: usize, out: *mut u8) {
+    let data = if len > 0 {
+        slice::from_raw_parts(data, len)
+    } else {
+        &[]
+    };
+
+    let hash = Sha1::digest(data);
+    slice::from_raw_parts_mut(out, 20).copy_from_slice(&hash_to_bytes(&hash));
+}
@@ -0,0 +1,239 @@
+use crate::{Digest, Hash, Sha1, Sha1Error};
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::io::{Seek, SeekFrom};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::{fs::File, path::Path};
+
+/// Builder for hashing files and readers with a configurable read buffer.
+///
+/// `Sha1::digest_file()`, `Sha1::digest_path()`, and `Sha1::digest_reader()` use a
+/// `FileHasher` with the default settings. A larger buffer means fewer read calls, which helps
+/// on spinning disks and network filesystems where each call is expensive.
+///
+/// Example usage:
+/// ```
+/// # use sha1::FileHasher;
+/// # fn f() -> std::io::Result<()> {
+/// let (hash, bytes) = FileHasher::new().buffer_size(1 << 20).digest_path("foo.txt")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FileHasher {
+    buffer_size: usize,
+    cancel: Option<Arc<AtomicBool>>,
+    #[cfg(feature = "readahead")]
+    read_ahead: bool,
+}
+
+impl FileHasher {
+    /// Size of the read buffer unless another is set with `FileHasher::buffer_size()`.
+    pub const DEFAULT_BUFFER_SIZE: usize = 128 * 1024;
+
+    /// Creates a new `FileHasher` with the default settings.
+    pub fn new() -> FileHasher {
+        FileHasher {
+            buffer_size: FileHasher::DEFAULT_BUFFER_SIZE,
+            cancel: None,
+            #[cfg(feature = "readahead")]
+            read_ahead: true,
+        }
+    }
+
+    /// Sets the size of the buffer that data is read into before it is hashed.
+    ///
+    /// Panics if `size` is 0.
+    pub fn buffer_size(mut self, size: usize) -> FileHasher {
+        assert!(size > 0, "buffer size must not be 0");
+        self.buffer_size = size;
+        self
+    }
+
+    /// Checks `flag` between reads, and stops hashing with `Sha1Error::Cancelled` once it is set,
+    /// such as by a stop button on another thread. The error holds the hash of everything read
+    /// so far, so hashing can be picked up later from where it stopped.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use sha1::{FileHasher, Sha1Error};
+    /// # use std::sync::{atomic::AtomicBool, Arc};
+    /// # fn f() -> Result<(), Sha1Error> {
+    /// let stop = Arc::new(AtomicBool::new(false));
+    /// let hasher = FileHasher::new().cancel_flag(stop.clone());
+    /// match hasher.digest_path("disk.img") {
+    ///     Ok((hash, bytes)) => println!("{}", sha1::hex::encode(&hash)),
+    ///     Err(Sha1Error::Cancelled(s)) => println!("stopped after {} bytes", s.bytes_processed()),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> FileHasher {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Sets whether files are hashed with read-ahead hints to the operating system, which are
+    /// given by default: on Linux, `posix_fadvise()` with `POSIX_FADV_SEQUENTIAL` and
+    /// `POSIX_FADV_NOREUSE`, and on macOS, `fcntl()` with `F_RDAHEAD` and `F_RDADVISE`. These
+    /// make the kernel read further ahead, and keep large files from pushing more useful data out
+    /// of the page cache. Other operating systems are given no hints.
+    #[cfg(feature = "readahead")]
+    pub fn read_ahead(mut self, read_ahead: bool) -> FileHasher {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Hashes everything from a reader. Returns the hash and the number of bytes read.
+    pub fn digest_reader<R: Read>(&self, reader: R) -> Result<(Hash, u64), Sha1Error> {
+        self.read_all(reader, |_| ())
+    }
+
+    /// Copies everything from a reader to a writer, hashing it on the way. Returns the number of
+    /// bytes copied and the hash.
+    ///
+    /// Cancellation with `FileHasher::cancel_flag()` is returned as an
+    /// `io::ErrorKind::InvalidData` error wrapping `Sha1Error::Cancelled`.
+    pub fn copy_and_hash<R, W>(&self, reader: R, mut writer: W) -> io::Result<(u64, Digest)>
+    where
+        R: Read,
+        W: Write,
+    {
+        let (hash, bytes) = self.read_chunks(reader, |data, _| writer.write_all(data))?;
+        Ok((bytes, Digest::from(hash)))
+    }
+
+    /// Hashes a whole file. Returns the hash and the number of bytes read.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_file(&self, file: &mut File) -> Result<(Hash, u64), Sha1Error> {
+        #[cfg(feature = "readahead")]
+        if self.read_ahead {
+            let offset = file.stream_position()?;
+            crate::advise::sequential(file, offset, 0);
+        }
+
+        self.read_all(file, |_| ())
+    }
+
+    /// Hashes `len` bytes of a file starting at `offset`, leaving the file positioned after
+    /// them. Returns the hash and the number of bytes read, which is less than `len` if the file
+    /// ends first.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_file_range(
+        &self,
+        file: &mut File,
+        offset: u64,
+        len: u64,
+    ) -> Result<(Hash, u64), Sha1Error> {
+        file.seek(SeekFrom::Start(offset))?;
+
+        #[cfg(feature = "readahead")]
+        if self.read_ahead && len > 0 {
+            crate::advise::sequential(file, offset, len);
+        }
+
+        self.read_all(file.take(len), |_| ())
+    }
+
+    /// Hashes a whole file by its path. Returns the hash and the number of bytes in the file.
+    ///
+    /// With the `mmap` feature enabled, large files are memory-mapped and hashed in place rather
+    /// than read into the buffer piece by piece. If another process truncates a mapped file
+    /// while it is being hashed, this process is killed by `SIGBUS`, so don't enable `mmap` if
+    /// files may be truncated during hashing.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_path<P: AsRef<Path>>(&self, path: P) -> Result<(Hash, u64), Sha1Error> {
+        let mut file = File::open(path)?;
+
+        #[cfg(feature = "mmap")]
+        {
+            // Mapping has a fixed setup cost, so only bother for files that are large enough
+            const MMAP_THRESHOLD: u64 = 1 << 20;
+
+            let len = file.metadata()?.len();
+            if len >= MMAP_THRESHOLD {
+                // Safety: the mapping is only read from while it is alive. If another process
+                // modifies the file during hashing, the hash will be wrong, like when reading
+                // the file. If it truncates the file, touching the pages past the new end
+                // raises SIGBUS and kills the process, which is why mmap is opt-in and
+                // documented as unsafe for files that may be truncated.
+                let map = unsafe { memmap2::Mmap::map(&file)? };
+                let mut s = Sha1::new();
+                for piece in map.chunks(self.buffer_size) {
+                    self.check_cancelled(&s)?;
+                    s.update(piece);
+                }
+                return Ok((s.try_finish()?, len));
+            }
+        }
+
+        self.digest_file(&mut file)
+    }
+
+    // Hashes everything from a reader, calling progress with the total bytes read after every
+    // read.
+    pub(crate) fn read_all<R, F>(
+        &self,
+        reader: R,
+        mut progress: F,
+    ) -> Result<(Hash, u64), Sha1Error>
+    where
+        R: Read,
+        F: FnMut(u64),
+    {
+        self.read_chunks(reader, |_, done| {
+            progress(done);
+            Ok(())
+        })
+    }
+
+    // Hashes everything from a reader, calling each with the data from every read and the total
+    // bytes read so far. Stops at the first error from each.
+    fn read_chunks<R, F>(&self, mut reader: R, mut each: F) -> Result<(Hash, u64), Sha1Error>
+    where
+        R: Read,
+        F: FnMut(&[u8], u64) -> io::Result<()>,
+    {
+        let mut s = Sha1::new();
+        let mut buf = vec![0; self.buffer_size];
+        let mut done = 0;
+
+        loop {
+            self.check_cancelled(&s)?;
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            s.update(&buf[..n]);
+            done += n as u64;
+            each(&buf[..n], done)?;
+        }
+
+        Ok((s.try_finish()?, done))
+    }
+
+    // Returns Sha1Error::Cancelled with the hash so far if the cancel flag is set.
+    fn check_cancelled(&self, s: &Sha1) -> Result<(), Sha1Error> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => {
+                Err(Sha1Error::Cancelled(Box::new(s.clone())))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for FileHasher {
+    /// Equivalent to `FileHasher::new()`.
+    fn default() -> FileHasher {
+        FileHasher::new()
+    }
+}
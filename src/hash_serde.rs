@@ -0,0 +1,147 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! `Sha1` implements `Serialize` and `Deserialize` using the state from `Sha1::export_state()`,
+//! so an in-progress hash can be stored and resumed.
+//!
+//! `Digest` is serialized as 40 lowercase hex digits in human-readable formats like JSON, and as
+//! its 20 bytes in binary formats.
+//!
+//! `Hash` is a plain array, so its representation is chosen by using one of the [`hex`] or
+//! [`bytes`] modules with `#[serde(with = "...")]`:
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Entry {
+//!     path: String,
+//!     #[serde(with = "sha1::hash_serde::hex")]
+//!     hash: sha1::Hash,
+//! }
+//! ```
+
+use crate::{Digest, Hash, Sha1};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::convert::TryInto;
+use std::fmt;
+
+/// Represents a `Hash` as a string of 40 hex digits. Lowercase is produced, and either case is
+/// accepted.
+pub mod hex {
+    use super::*;
+
+    /// Serializes a `Hash` as a hex string.
+    pub fn serialize<S: Serializer>(hash: &Hash, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&crate::hex::encode(hash))
+    }
+
+    /// Deserializes a `Hash` from a hex string.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hash, D::Error> {
+        deserializer.deserialize_str(HexVisitor)
+    }
+}
+
+/// Represents a `Hash` as its 20 big-endian bytes.
+pub mod bytes {
+    use super::*;
+
+    /// Serializes a `Hash` as 20 bytes.
+    pub fn serialize<S: Serializer>(hash: &Hash, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&crate::hash_to_bytes(hash))
+    }
+
+    /// Deserializes a `Hash` from 20 bytes.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hash, D::Error> {
+        let bytes = deserializer.deserialize_bytes(ByteVisitor(20))?;
+        let word = |i: usize| u32::from_be_bytes(bytes[i * 4..(i + 1) * 4].try_into().unwrap());
+        Ok([word(0), word(1), word(2), word(3), word(4)])
+    }
+}
+
+impl Serialize for Digest {
+    /// Serializes the digest as a hex string in human-readable formats, and as bytes otherwise.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            hex::serialize(&self.words(), serializer)
+        } else {
+            bytes::serialize(&self.words(), serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Digest {
+    /// Deserializes a digest from a hex string in human-readable formats, and from bytes
+    /// otherwise.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Digest, D::Error> {
+        let hash = if deserializer.is_human_readable() {
+            hex::deserialize(deserializer)?
+        } else {
+            bytes::deserialize(deserializer)?
+        };
+
+        Ok(Digest::from(hash))
+    }
+}
+
+impl Serialize for Sha1 {
+    /// Serializes the state from `Sha1::export_state()` as bytes.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.export_state())
+    }
+}
+
+impl<'de> Deserialize<'de> for Sha1 {
+    /// Deserializes bytes as a state for `Sha1::import_state()`.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Sha1, D::Error> {
+        let bytes = deserializer.deserialize_bytes(ByteVisitor(Sha1::STATE_LEN))?;
+        Sha1::import_state(bytes[..].try_into().unwrap())
+            .map_err(|_| de::Error::custom("invalid SHA-1 state"))
+    }
+}
+
+// Accepts 40 hex digits, borrowed from the input or not.
+struct HexVisitor;
+
+impl<'de> Visitor<'de> for HexVisitor {
+    type Value = Hash;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a SHA-1 hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Hash, E> {
+        crate::hex::decode(v).map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+// Accepts exactly the given number of bytes, either as a byte string or as a sequence of
+// numbers for formats without byte strings, like JSON.
+struct ByteVisitor(usize);
+
+impl<'de> Visitor<'de> for ByteVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", self.0)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        if v.len() != self.0 {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        Ok(v.to_vec())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(self.0);
+
+        while let Some(byte) = seq.next_element()? {
+            if bytes.len() == self.0 {
+                return Err(de::Error::invalid_length(self.0 + 1, &self));
+            }
+
+            bytes.push(byte);
+        }
+
+        self.visit_bytes(&bytes)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::sha0::Sha0;
+use crate::{CheckedSha1, Hash, Sha1, Sha1Error};
+
+/// Common interface of the streaming hashers in this crate, for writing code that works with any
+/// of them.
+///
+/// Hashers that can fail, like `CheckedSha1`, remember the failure and return it from
+/// `finish()` and `finish_reset()` as part of their `Output`.
+///
+/// Example usage:
+/// ```
+/// # use sha1::{sha0::Sha0, Sha1, StreamingHasher};
+/// fn hash_lines<H: StreamingHasher>(hasher: &mut H, text: &str) -> Vec<H::Output> {
+///     text.lines()
+///         .map(|line| {
+///             hasher.update(line.as_bytes());
+///             hasher.finish_reset()
+///         })
+///         .collect()
+/// }
+///
+/// let sha1 = hash_lines(&mut Sha1::new(), "a\nb");
+/// let sha0 = hash_lines(&mut Sha0::new(), "a\nb");
+/// ```
+pub trait StreamingHasher {
+    /// Type of a finished hash.
+    type Output;
+
+    /// Adds data to the hash.
+    fn update(&mut self, data: &[u8]);
+
+    /// Finishes the hash and returns it.
+    fn finish(self) -> Self::Output
+    where
+        Self: Sized;
+
+    /// Finishes the hash and returns it, resetting the hasher to hash another message.
+    fn finish_reset(&mut self) -> Self::Output;
+
+    /// Discards all data added so far.
+    fn reset(&mut self);
+
+    /// Returns the length of a finished hash in bytes.
+    fn output_len(&self) -> usize;
+}
+
+impl StreamingHasher for Sha1 {
+    type Output = Hash;
+
+    fn update(&mut self, data: &[u8]) {
+        Sha1::update(self, data)
+    }
+
+    fn finish(self) -> Hash {
+        Sha1::finish(self)
+    }
+
+    fn finish_reset(&mut self) -> Hash {
+        Sha1::finish_reset(self)
+    }
+
+    fn reset(&mut self) {
+        Sha1::reset(self)
+    }
+
+    fn output_len(&self) -> usize {
+        20
+    }
+}
+
+impl StreamingHasher for Sha0 {
+    type Output = Hash;
+
+    fn update(&mut self, data: &[u8]) {
+        Sha0::update(self, data)
+    }
+
+    fn finish(self) -> Hash {
+        Sha0::finish(self)
+    }
+
+    fn finish_reset(&mut self) -> Hash {
+        Sha0::finish_reset(self)
+    }
+
+    fn reset(&mut self) {
+        Sha0::reset(self)
+    }
+
+    fn output_len(&self) -> usize {
+        20
+    }
+}
+
+impl StreamingHasher for CheckedSha1 {
+    type Output = Result<Hash, Sha1Error>;
+
+    /// Adds data to the hash. A mismatch is kept and returned when the hash is finished.
+    fn update(&mut self, data: &[u8]) {
+        // The error is latched, so finish() still returns it
+        let _ = CheckedSha1::update(self, data);
+    }
+
+    fn finish(self) -> Result<Hash, Sha1Error> {
+        CheckedSha1::finish(self)
+    }
+
+    fn finish_reset(&mut self) -> Result<Hash, Sha1Error> {
+        CheckedSha1::finish_reset(self)
+    }
+
+    fn reset(&mut self) {
+        CheckedSha1::reset(self)
+    }
+
+    fn output_len(&self) -> usize {
+        20
+    }
+}
@@ -0,0 +1,25 @@
+//! Conversion between hashes and hex strings.
+
+use crate::{Hash, Sha1Error};
+
+/// Formats a hash as 40 lowercase hex digits.
+#[cfg(feature = "std")]
+pub fn encode(hash: &Hash) -> String {
+    hash.iter().map(|word| format!("{:08x}", word)).collect()
+}
+
+/// Parses 40 hex digits of either case into a hash. Returns `Sha1Error::InvalidHex` if `s` is
+/// not exactly 40 hex digits.
+pub fn decode(s: &str) -> Result<Hash, Sha1Error> {
+    if s.len() != 40 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Sha1Error::InvalidHex);
+    }
+
+    let mut hash = [0; 5];
+    for (i, word) in hash.iter_mut().enumerate() {
+        *word =
+            u32::from_str_radix(&s[i * 8..(i + 1) * 8], 16).map_err(|_| Sha1Error::InvalidHex)?;
+    }
+
+    Ok(hash)
+}
@@ -0,0 +1,148 @@
+//! HMAC-SHA1 message authentication (RFC 2104).
+//!
+//! Example usage:
+//! ```
+//! # use sha1::hmac::{Hmac, HmacKey};
+//! // Authenticate a message in chunks
+//! let mut h = Hmac::new(b"key");
+//! h.update(b"First part of message");
+//! h.update(b"Second part of message");
+//! let tag = h.finish();
+//!
+//! // Authenticate a message in one line
+//! let tag = Hmac::mac(b"key", b"Hello, world");
+//!
+//! // Authenticate many messages with the same key
+//! let key = HmacKey::new(b"key");
+//! let tags: Vec<_> = ["a", "b", "c"].iter().map(|m| key.mac(m)).collect();
+//! ```
+
+use crate::{compress, hash_to_bytes, Digest, Digest96, Hash, Sha1, INITIAL_STATE};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// HMAC-SHA1 context. Represents one single message authentication code.
+///
+/// Can be cloned to authenticate several messages with the same key without processing the key
+/// again.
+#[derive(Clone)]
+pub struct Hmac {
+    // Hashes with the inner and outer padded keys already added
+    inner: Sha1,
+    outer: Sha1,
+}
+
+impl Hmac {
+    /// Creates a new `Hmac` struct with the given key. Keys longer than 64 bytes are hashed first,
+    /// as specified by RFC 2104.
+    pub fn new<K: AsRef<[u8]>>(key: K) -> Hmac {
+        Hmac::from_key(&HmacKey::new(key))
+    }
+
+    /// Creates a new `Hmac` struct from a key that has already been processed.
+    pub fn from_key(key: &HmacKey) -> Hmac {
+        Hmac {
+            inner: Sha1::from_midstate(key.inner, 1),
+            outer: Sha1::from_midstate(key.outer, 1),
+        }
+    }
+
+    /// Utility function to simplify `Hmac` use when the whole message is available at once.
+    pub fn mac<K: AsRef<[u8]>, D: AsRef<[u8]>>(key: K, data: D) -> Hash {
+        let mut h = Hmac::new(key);
+        h.update(data);
+        h.finish()
+    }
+
+    /// Adds data to the authenticated message.
+    pub fn update<D: AsRef<[u8]>>(&mut self, data: D) {
+        self.inner.update(data);
+    }
+
+    /// Finishes the message and returns the authentication code.
+    pub fn finish(self) -> Hash {
+        let mut outer = self.outer;
+        outer.update(hash_to_bytes(&self.inner.finish()));
+        outer.finish()
+    }
+
+    /// Finishes the message and returns the authentication code truncated to its leftmost 96
+    /// bits, as HMAC-SHA-1-96 (RFC 2404).
+    pub fn finish96(self) -> Digest96 {
+        Digest::from(self.finish()).truncate96()
+    }
+}
+
+/// HMAC-SHA1 key with its inner and outer padded blocks already compressed.
+///
+/// Authenticating a message with an `HmacKey` skips processing the key, so a message of up to 55
+/// bytes costs only two compressions.
+#[derive(Clone)]
+pub struct HmacKey {
+    // Hash value words after the inner and outer padded keys
+    inner: Hash,
+    outer: Hash,
+}
+
+impl HmacKey {
+    /// Processes a key. Keys longer than 64 bytes are hashed first, as specified by RFC 2104.
+    pub fn new<K: AsRef<[u8]>>(key: K) -> HmacKey {
+        let key = key.as_ref();
+        let mut block = [0; 64];
+
+        if key.len() > 64 {
+            block[..20].copy_from_slice(&hash_to_bytes(&Sha1::digest(key)));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = INITIAL_STATE;
+        let mut outer = INITIAL_STATE;
+        let pad = |state: &mut Hash, byte: u8| {
+            #[allow(unused_mut)]
+            let mut padded = block.map(|b| b ^ byte);
+            compress(state, &padded);
+
+            #[cfg(feature = "zeroize")]
+            zeroize::Zeroize::zeroize(&mut padded);
+        };
+        pad(&mut inner, 0x36);
+        pad(&mut outer, 0x5c);
+
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut block);
+
+        HmacKey { inner, outer }
+    }
+
+    /// Returns the authentication code of a whole message.
+    pub fn mac<D: AsRef<[u8]>>(&self, data: D) -> Hash {
+        let mut h = Hmac::from_key(self);
+        h.update(data);
+        h.finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for HmacKey {
+    /// Zeroes the processed key.
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.inner);
+        zeroize::Zeroize::zeroize(&mut self.outer);
+    }
+}
+
+#[cfg(feature = "std")]
+impl Write for Hmac {
+    /// Adds all data to the message by calling `self.update(data)` and returns
+    /// `Ok(data.len())`.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+// I/O for Sha1: hashing files and readers, and the std::io::Write and async writer impls.
+
+use crate::{Digest, FileHasher, Hash, Sha1, Sha1Error};
+use std::io::{self, Read, Write};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::{fs::File, path::Path};
+
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+
+#[cfg(all(feature = "uring", target_os = "linux"))]
+use crate::uring;
+
+impl Sha1 {
+    /// Utility function to simplify `Sha1` use when hashing a whole file. Returns the hash and
+    /// the number of bytes read.
+    ///
+    /// Equivalent to `FileHasher::new().digest_file(file)`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_file(file: &mut File) -> Result<(Hash, u64), Sha1Error> {
+        FileHasher::new().digest_file(file)
+    }
+
+    /// Utility function to simplify `Sha1` use when hashing part of a file, such as one extent of
+    /// a disk image. Returns the hash and the number of bytes read, which is less than `len` if
+    /// the file ends first.
+    ///
+    /// Equivalent to `FileHasher::new().digest_file_range(file, offset, len)`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_file_range(
+        file: &mut File,
+        offset: u64,
+        len: u64,
+    ) -> Result<(Hash, u64), Sha1Error> {
+        FileHasher::new().digest_file_range(file, offset, len)
+    }
+
+    /// Utility function to simplify `Sha1` use when hashing a whole file by its path. Returns the
+    /// hash and the number of bytes in the file.
+    ///
+    /// Equivalent to `FileHasher::new().digest_path(path)`. With the `mmap` feature enabled,
+    /// large files are memory-mapped and hashed in place rather than read into a buffer piece by
+    /// piece, and truncating such a file while it is hashed kills the process with `SIGBUS`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_path<P: AsRef<Path>>(path: P) -> Result<(Hash, u64), Sha1Error> {
+        FileHasher::new().digest_path(path)
+    }
+
+    /// Same as `Sha1::digest_path()`, but reads the file with io_uring, keeping several reads in
+    /// flight while earlier parts of the file are hashed. Helps with large files on fast drives,
+    /// where waiting for each read before hashing it takes longer than the hashing itself.
+    ///
+    /// Falls back to `Sha1::digest_path()` if the kernel doesn't support io_uring or it is
+    /// disabled.
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    pub fn digest_path_uring<P: AsRef<Path>>(path: P) -> Result<(Hash, u64), Sha1Error> {
+        uring::digest_path(path)
+    }
+
+    /// Same as `Sha1::digest_file()`, but calls `progress(bytes_done, total)` after every read,
+    /// where `total` is the length of the file. Useful for driving a progress bar while hashing
+    /// large files.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_file_progress<F>(
+        file: &mut File,
+        mut progress: F,
+    ) -> Result<(Hash, u64), Sha1Error>
+    where
+        F: FnMut(u64, u64),
+    {
+        let total = file.metadata()?.len();
+        FileHasher::new().read_all(file, |done| progress(done, total))
+    }
+
+    /// Same as `Sha1::digest_path()`, but calls `progress(bytes_done, total)` after every read.
+    /// See `Sha1::digest_file_progress()`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn digest_path_progress<P, F>(path: P, progress: F) -> Result<(Hash, u64), Sha1Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(u64, u64),
+    {
+        Sha1::digest_file_progress(&mut File::open(path)?, progress)
+    }
+
+    /// Utility function to simplify `Sha1` use when hashing everything from a reader, such as a
+    /// `TcpStream` or `io::stdin()`. Returns the hash and the number of bytes read.
+    ///
+    /// Equivalent to `FileHasher::new().digest_reader(reader)`, which reads into a large buffer
+    /// and is otherwise equivalent to:
+    /// ```
+    /// # use sha1::Sha1;
+    /// # fn f<R: std::io::Read>(mut reader: R) -> std::io::Result<([u32; 5], u64)> {
+    /// let mut s = Sha1::new();
+    /// let bytes = std::io::copy(&mut reader, &mut s)?;
+    /// let hash = s.finish();
+    /// Ok((hash, bytes))
+    /// # }
+    /// ```
+    pub fn digest_reader<R: Read>(reader: R) -> Result<(Hash, u64), Sha1Error> {
+        FileHasher::new().digest_reader(reader)
+    }
+
+    /// Utility function to simplify `Sha1` use when storing data and checksumming it at the same
+    /// time, such as when saving a download to a file. Copies everything from a reader to a
+    /// writer and returns the number of bytes copied and the hash.
+    ///
+    /// Equivalent to `FileHasher::new().copy_and_hash(reader, writer)`.
+    pub fn copy_and_hash<R, W>(reader: R, writer: W) -> io::Result<(u64, Digest)>
+    where
+        R: Read,
+        W: Write,
+    {
+        FileHasher::new().copy_and_hash(reader, writer)
+    }
+
+    /// Async equivalent of `Sha1::digest_reader`, for hashing everything from a
+    /// `futures::AsyncRead` such as an HTTP response body. Returns the hash and the number of
+    /// bytes read.
+    #[cfg(feature = "futures")]
+    pub async fn digest_async_reader<R>(mut reader: R) -> Result<(Hash, u64), Sha1Error>
+    where
+        R: futures_util::io::AsyncRead + Unpin,
+    {
+        use futures_util::io::AsyncReadExt;
+
+        let mut s = Sha1::new();
+        let mut buf = [0; 8192];
+        let mut bytes = 0;
+
+        loop {
+            let n = match reader.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            s.update(&buf[..n]);
+            bytes += n as u64;
+        }
+
+        Ok((s.try_finish()?, bytes))
+    }
+
+    /// Async hashing of a `futures::Stream` of byte buffers, such as an HTTP response body from
+    /// hyper or reqwest. Returns the hash and the number of bytes received, or the first error
+    /// from the stream.
+    ///
+    /// Panics if more than 2^64 - 1 bits were received, like `Sha1::finish()`.
+    #[cfg(feature = "futures")]
+    pub async fn digest_stream<S, B, E>(stream: S) -> Result<(Hash, u64), E>
+    where
+        S: futures_util::stream::Stream<Item = Result<B, E>>,
+        B: AsRef<[u8]>,
+    {
+        use futures_util::stream::StreamExt;
+
+        let mut stream = std::pin::pin!(stream);
+        let mut s = Sha1::new();
+        let mut bytes = 0;
+
+        while let Some(buf) = stream.next().await {
+            let buf = buf?;
+            s.update(&buf);
+            bytes += buf.as_ref().len() as u64;
+        }
+
+        Ok((s.finish(), bytes))
+    }
+
+    /// Reads from a reader into the given hash until it ends, or until `limit` bytes have been
+    /// read if a limit is given. Returns the number of bytes read.
+    ///
+    /// Never reads past the limit, so that a stream of framed sections can be hashed section by
+    /// section, reading whatever comes between them in between calls.
+    pub fn update_from_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        limit: Option<u64>,
+    ) -> io::Result<u64> {
+        let mut buf = [0; 8192];
+        let mut done = 0;
+
+        loop {
+            let want = match limit {
+                Some(limit) => (limit - done).min(buf.len() as u64) as usize,
+                None => buf.len(),
+            };
+            if want == 0 {
+                return Ok(done);
+            }
+
+            match reader.read(&mut buf[..want]) {
+                Ok(0) => return Ok(done),
+                Ok(n) => {
+                    self.update(&buf[..n]);
+                    done += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Write for Sha1 {
+    /// Writes all data to hasher by calling `self.update(data)` and returns `Ok(data.len())`.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`. There is nothing that flush would make sense to do.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for Sha1 {
+    /// Writes all data to hasher by calling `self.update(data)` and returns
+    /// `Poll::Ready(Ok(data.len()))`. Never blocks.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().update(data);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    /// Does nothing and returns `Poll::Ready(Ok(()))`.
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Does nothing and returns `Poll::Ready(Ok(()))`. The hash is still available from
+    /// `Sha1::finish()` after shutdown.
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
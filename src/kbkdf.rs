@@ -0,0 +1,48 @@
+//! Key derivation in counter mode from NIST SP 800-108, with HMAC-SHA1 as the PRF.
+//!
+//! Each 20 byte block of output is the HMAC of a 32 bit big-endian block counter starting at 1,
+//! the label, a zero byte, the context, and the output length in bits as a 32 bit big-endian
+//! number.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::kbkdf;
+//! let mut session_key = [0; 16];
+//! kbkdf::counter_mode(b"master key", b"ENC", b"session 42", &mut session_key);
+//! ```
+
+use crate::hash_to_bytes;
+use crate::hmac::Hmac;
+
+/// Fills `out` with key material derived from `key`, bound to the purpose given by `label` and
+/// the parties or session given by `context`.
+///
+/// Panics if `out` is longer than 2^29 - 1 bytes, whose length in bits doesn't fit the 32 bit
+/// length field.
+pub fn counter_mode<K, L, C>(key: K, label: L, context: C, out: &mut [u8])
+where
+    K: AsRef<[u8]>,
+    L: AsRef<[u8]>,
+    C: AsRef<[u8]>,
+{
+    assert!(
+        out.len() < 1 << 29,
+        "output is too long for the 32 bit length field"
+    );
+    let bits = out.len() as u32 * 8;
+
+    // Everything after the counter is the same for every block
+    let mut fixed = Vec::new();
+    fixed.extend_from_slice(label.as_ref());
+    fixed.push(0);
+    fixed.extend_from_slice(context.as_ref());
+    fixed.extend_from_slice(&bits.to_be_bytes());
+
+    let prf = Hmac::new(key);
+    for (i, block) in out.chunks_mut(20).enumerate() {
+        let mut h = prf.clone();
+        h.update((i as u32 + 1).to_be_bytes());
+        h.update(&fixed);
+        block.copy_from_slice(&hash_to_bytes(&h.finish())[..block.len()]);
+    }
+}
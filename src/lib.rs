@@ -1,19 +1,145 @@
 #![allow(clippy::unreadable_literal)]
 #![allow(clippy::many_single_char_names)]
+#![allow(clippy::needless_range_loop)]
+#![allow(clippy::manual_rotate)]
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[cfg(test)]
+// The crate is split into the algorithm itself (lib.rs, compress.rs and the backends), I/O on top
+// of it (io.rs, file.rs, and the readers and writers), and extras built from both. Without the
+// default std feature, the crate is no_std and only has what needs neither I/O nor allocation.
+
+#[cfg(all(test, feature = "std"))]
 mod tests;
 
-use std::convert::TryInto;
-use std::fs::File;
-use std::io::{self, Write};
+#[cfg(all(
+    feature = "readahead",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod advise;
+mod aggregate;
+#[cfg(all(
+    feature = "asm",
+    target_arch = "x86_64",
+    target_feature = "sha",
+    target_feature = "sse4.1"
+))]
+mod asm;
+mod backend;
+#[cfg(feature = "std")]
+mod base32;
+#[cfg(feature = "std")]
+mod base64;
+#[cfg(all(
+    feature = "cas",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+pub mod cas;
+#[cfg(feature = "std")]
+pub mod chain;
+mod checked;
+mod compress;
+mod digest;
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+pub mod dir;
+#[cfg(feature = "std")]
+pub mod dkim;
+pub mod drbg;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "serde")]
+pub mod hash_serde;
+mod hasher;
+pub mod hex;
+pub mod hmac;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "std")]
+pub mod kbkdf;
+mod macros;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "std")]
+pub mod merkle;
+#[cfg(feature = "std")]
+pub mod mgf1;
+#[cfg(feature = "std")]
+pub mod oauth1;
+#[cfg(feature = "std")]
+mod offload;
+#[cfg(feature = "openssl")]
+pub mod openssl;
+pub mod pbkdf2;
+#[cfg(feature = "std")]
+pub mod pieces;
+pub mod pkcs1;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+mod resume;
+pub mod scram;
+pub mod sha0;
+#[cfg(feature = "portable-simd")]
+mod simd;
+pub mod snmp;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "std")]
+pub mod tls_prf;
+#[cfg(feature = "std")]
+pub mod torrent;
+#[cfg(all(feature = "uring", target_os = "linux"))]
+mod uring;
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm32;
+#[cfg(feature = "std")]
+mod writer;
+
+pub use aggregate::AggregateDigest;
+pub use backend::Backend;
+pub use checked::CheckedSha1;
+pub use compress::{compress, INITIAL_STATE};
+pub use digest::{Digest, Digest96};
+pub use error::{SelfTestError, Sha1Error};
+#[cfg(feature = "std")]
+pub use file::FileHasher;
+pub use hasher::StreamingHasher;
+#[cfg(feature = "std")]
+pub use offload::{OffloadHandle, OffloadHasher};
+#[cfg(feature = "std")]
+pub use reader::{HashingReader, VerifyingReader};
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+pub use resume::ResumableFileHash;
+#[cfg(feature = "std")]
+pub use sync::SyncSha1;
+#[cfg(feature = "std")]
+pub use writer::{HashingWriter, VerifyingWriter};
+
+use compress::compress_chunk;
+use core::convert::TryInto;
+use core::fmt;
+use core::iter::FromIterator;
 
 /// Type alias for `[u32; 5]` for more readable usage of hash results.
 pub type Hash = [u32; 5];
 
 /// SHA-1 Hash context. Represents one single hash.
 ///
-/// Can be reset and reused, but cannot hash more than one thing at once.
+/// Can be reset and reused before it is finished, but cannot hash more than one thing at once.
 /// Can be cloned if multiple pieces of data share a common prefix.
 ///
 /// Example usage:
@@ -45,6 +171,9 @@ pub struct Sha1 {
     // Bytes currently free in buffer
     used: u8,
 
+    // Bits of a partial final byte at chunk[used], from update_bits()
+    extra_bits: u8,
+
     // Number of chunks that have been processed in the past
     chunks_processed: u64,
 
@@ -57,11 +186,12 @@ pub struct Sha1 {
 }
 
 impl Sha1 {
-    /// Creates a new empty `Sha1` struct.
-    pub fn new() -> Sha1 {
+    /// Creates a new empty `Sha1` struct. Usable in `const` and `static` contexts.
+    pub const fn new() -> Sha1 {
         Sha1 {
             chunk: [0; 64],
             used: 0,
+            extra_bits: 0,
             chunks_processed: 0,
             h0: 0x67452301,
             h1: 0xEFCDAB89,
@@ -73,9 +203,16 @@ impl Sha1 {
 
     /// Re-initializes internal values to act like a brand new `Sha1` struct.  May be slightly
     /// faster than calling `Sha1::new()` because the internal 64 byte chunk is not zeroed.
+    ///
+    /// With the `zeroize` feature enabled, the chunk is zeroed anyway so that previously hashed
+    /// data does not linger in memory.
     pub fn reset(&mut self) {
         // Does not reset self.chunk because it shouldn't matter
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.chunk);
+
         self.used = 0;
+        self.extra_bits = 0;
         self.chunks_processed = 0;
         self.h0 = 0x67452301;
         self.h1 = 0xEFCDAB89;
@@ -84,6 +221,62 @@ impl Sha1 {
         self.h4 = 0xC3D2E1F0;
     }
 
+    /// Creates a `Sha1` struct that continues from an existing hash of a `message_len` byte
+    /// message, as if the message and its padding had already been hashed.
+    ///
+    /// This performs a length-extension: further data passed to `Sha1::update()` produces the
+    /// hash of the original message, followed by `Sha1::padding(message_len)`, followed by the
+    /// new data. The original message itself does not need to be known.
+    ///
+    /// Returns `Sha1Error::LengthOverflow` if the message and its padding are more than 2^64 - 1
+    /// bits, which is when `message_len` is more than 2^61 - 73 bytes.
+    pub fn from_digest(hash: Hash, message_len: u64) -> Result<Sha1, Sha1Error> {
+        let padded_len = message_len
+            .checked_add(padding_len(message_len))
+            .filter(|len| len.checked_mul(8).is_some())
+            .ok_or(Sha1Error::LengthOverflow)?;
+
+        Ok(Sha1 {
+            chunk: [0; 64],
+            used: 0,
+            extra_bits: 0,
+            chunks_processed: padded_len / 64,
+            h0: hash[0],
+            h1: hash[1],
+            h2: hash[2],
+            h3: hash[3],
+            h4: hash[4],
+        })
+    }
+
+    // Creates a `Sha1` struct that continues from the hash value words after `chunks` whole
+    // chunks have been processed.
+    pub(crate) const fn from_midstate(h: Hash, chunks: u64) -> Sha1 {
+        Sha1 {
+            chunk: [0; 64],
+            used: 0,
+            extra_bits: 0,
+            chunks_processed: chunks,
+            h0: h[0],
+            h1: h[1],
+            h2: h[2],
+            h3: h[3],
+            h4: h[4],
+        }
+    }
+
+    /// Returns the padding that is appended to a `message_len` byte message before its final
+    /// chunk is processed: a 0x80 byte, zeroes, and the big-endian message length in bits.
+    #[cfg(feature = "std")]
+    pub fn padding(message_len: u64) -> Vec<u8> {
+        let mut padding = vec![0; padding_len(message_len) as usize];
+
+        padding[0] = 0x80;
+        let len = padding.len();
+        padding[len - 8..].copy_from_slice(&message_len.wrapping_mul(8).to_be_bytes());
+        padding
+    }
+
     /// Utility function to simplify `Sha1` use when all data is available at once.
     ///
     /// Equivalent to:
@@ -101,31 +294,204 @@ impl Sha1 {
         s.finish()
     }
 
-    /// Utility function to simplify `Sha1` use when hashing a whole file.
+    /// Utility function to simplify `Sha1` use when a message is made of several parts, such as
+    /// a header, payload, and footer. Returns the hash of the parts concatenated, without
+    /// concatenating them.
     ///
-    /// Equivalent to:
+    /// Example usage:
     /// ```
     /// # use sha1::Sha1;
-    /// # use std::fs::File;
-    /// # fn f() -> std::io::Result<([u32; 5], u64)> {
-    /// # let mut file = File::open("foo.txt")?;
-    /// let mut s = Sha1::new();
-    /// let bytes = std::io::copy(&mut file, &mut s)?;
-    /// let hash = s.finish();
-    /// Ok((hash, bytes))
-    /// # }
-    pub fn digest_file(file: &mut File) -> io::Result<(Hash, u64)> {
+    /// let hash = Sha1::digest_parts([&b"HEADER"[..], b"payload", b"FOOTER"]);
+    /// assert_eq!(hash, Sha1::digest(b"HEADERpayloadFOOTER"));
+    /// ```
+    pub fn digest_parts<I>(parts: I) -> Hash
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut s = Sha1::new();
+        s.update_iter(parts);
+        s.finish()
+    }
+
+    /// Utility function to hash a string encoded as UTF-16LE, as used for the wide strings of
+    /// many formats and protocols that come from Windows. See `Sha1::update_utf16le()`.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use sha1::Sha1;
+    /// assert_eq!(Sha1::digest_utf16le("hi"), Sha1::digest(b"h\0i\0"));
+    /// ```
+    pub fn digest_utf16le(s: &str) -> Hash {
+        let mut sha = Sha1::new();
+        sha.update_utf16le(s);
+        sha.finish()
+    }
+
+    /// Same as `Sha1::digest()`, but usable in `const` contexts so that hashes of constant data
+    /// can be computed at compile time. Returns the hash as bytes.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use sha1::Sha1;
+    /// const HELLO: [u8; 20] = Sha1::digest_const(b"Hello, world");
+    /// assert_eq!(HELLO, sha1::Digest::from(Sha1::digest(b"Hello, world")).bytes());
+    /// ```
+    pub const fn digest_const(data: &[u8]) -> [u8; 20] {
+        let mut h = INITIAL_STATE;
+        let mut chunk = [0; 64];
+        let mut i = 0;
+
+        // Process every whole chunk
+        while i + 64 <= data.len() {
+            let mut j = 0;
+            while j < 64 {
+                chunk[j] = data[i + j];
+                j += 1;
+            }
+            h = compress_chunk::<false>(h, &chunk);
+            i += 64;
+        }
+
+        // Copy the rest of the data and the 0x80 byte into the last chunk
+        let rest = data.len() - i;
+        let mut j = 0;
+        while j < 64 {
+            chunk[j] = if j < rest {
+                data[i + j]
+            } else if j == rest {
+                0x80
+            } else {
+                0
+            };
+            j += 1;
+        }
+
+        // The message length needs its own chunk if there is no room after the 0x80 byte
+        if rest >= 56 {
+            h = compress_chunk::<false>(h, &chunk);
+            chunk = [0; 64];
+        }
+
+        let length = (data.len() as u64 * 8).to_be_bytes();
+        let mut j = 0;
+        while j < 8 {
+            chunk[56 + j] = length[j];
+            j += 1;
+        }
+        h = compress_chunk::<false>(h, &chunk);
+
+        let mut bytes = [0; 20];
+        let mut j = 0;
+        while j < 20 {
+            bytes[j] = h[j / 4].to_be_bytes()[j % 4];
+            j += 1;
+        }
+
+        bytes
+    }
+
+    /// Hashes each item separately and returns their digests, in order. One `Sha1` struct is
+    /// reset and reused for all of them rather than creating a new one per item.
+    #[cfg(feature = "std")]
+    pub fn digest_many<I>(items: I) -> Vec<Digest>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
         let mut s = Sha1::new();
-        let bytes = io::copy(file, &mut s)?;
-        let hash = s.finish();
-        Ok((hash, bytes))
+        items
+            .into_iter()
+            .map(|item| {
+                s.update(item);
+                Digest::from(s.finish_reset())
+            })
+            .collect()
+    }
+
+    /// Returns the backend that hashing currently runs on.
+    ///
+    /// This is the fastest backend that the target was built with, unless the portable backend
+    /// is forced with `Sha1::force_portable()` or by setting the `SHA1_FORCE_SOFTWARE`
+    /// environment variable to anything other than `0`.
+    pub fn active_backend() -> Backend {
+        backend::active()
+    }
+
+    /// Forces all hashing in the process to use the portable backend, or with `false`, allows
+    /// faster backends again. Overrides the `SHA1_FORCE_SOFTWARE` environment variable.
+    ///
+    /// Every backend gives the same results, so this is only useful for reproducing bugs and
+    /// benchmarking the backends against each other.
+    pub fn force_portable(force: bool) {
+        backend::force_portable(force)
+    }
+
+    /// Checks the active backend against the known-answer tests of FIPS 180-1 and RFC 3174, for
+    /// environments that require a power-on health check, or to catch a miscompiled backend.
+    /// Each message is hashed both with `Sha1::update()` and a byte at a time through `Extend`,
+    /// so that the buffering of partial blocks is checked too.
+    ///
+    /// Returns a `SelfTestError` naming the first vector that hashed wrong.
+    pub fn self_test() -> Result<(), SelfTestError> {
+        // TEST1 to TEST4 of RFC 3174
+        const TESTS: [(&str, &[u8], usize, Hash); 4] = [
+            (
+                "TEST1",
+                b"abc",
+                1,
+                [0xa9993e36, 0x4706816a, 0xba3e2571, 0x7850c26c, 0x9cd0d89d],
+            ),
+            (
+                "TEST2",
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                1,
+                [0x84983e44, 0x1c3bd26e, 0xbaae4aa1, 0xf95129e5, 0xe54670f1],
+            ),
+            (
+                "TEST3",
+                b"a",
+                1_000_000,
+                [0x34aa973c, 0xd4c4daa4, 0xf61eeb2b, 0xdbad2731, 0x6534016f],
+            ),
+            (
+                "TEST4",
+                b"0123456701234567012345670123456701234567012345670123456701234567",
+                10,
+                [0xdea356a2, 0xcddd90c7, 0xa7ecedc5, 0xebb56393, 0x4f460452],
+            ),
+        ];
+
+        for &(vector, data, repeat, expected) in &TESTS {
+            let mut whole = Sha1::new();
+            let mut bytes = Sha1::new();
+            for _ in 0..repeat {
+                whole.update(data);
+                bytes.extend(data);
+            }
+
+            for actual in [whole.finish(), bytes.finish()] {
+                if actual != expected {
+                    return Err(SelfTestError {
+                        vector,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Ok(())
     }
 
     /// Adds data to the given hash. Hashing work is done for every 64 bytes passed to the struct
     /// through this function (including from previous calls).
+    ///
+    /// Panics if called after `Sha1::update_bits()` added a partial byte.
     // This function must always leave at least 1 byte free in the chunk when it's finished.
     pub fn update<D: AsRef<[u8]>>(&mut self, data: D) {
         let data = data.as_ref();
+        self.assert_whole_bytes();
 
         // Chunk vars
         let mut used = self.used as usize;
@@ -152,16 +518,166 @@ impl Sha1 {
         self.used = (used + remaining) as u8;
     }
 
+    /// Adds the first `bit_len` bits of `data` to the given hash, for messages whose length is not
+    /// a multiple of 8 bits. Bits are taken from the most significant end of each byte.
+    ///
+    /// If `bit_len` is not a multiple of 8, the message must end there: adding more data
+    /// afterwards panics, and the hash should be finished.
+    ///
+    /// Panics if `bit_len` is greater than the number of bits in `data`.
+    pub fn update_bits<D: AsRef<[u8]>>(&mut self, data: D, bit_len: usize) {
+        let data = data.as_ref();
+        assert!(bit_len <= data.len() * 8, "bit length is longer than data");
+
+        self.update(&data[..bit_len / 8]);
+
+        let extra_bits = (bit_len % 8) as u8;
+        if extra_bits > 0 {
+            // update() always leaves at least 1 byte free, so the partial byte fits
+            self.chunk[self.used as usize] = data[bit_len / 8] & !(0xff >> extra_bits);
+            self.extra_bits = extra_bits;
+        }
+    }
+
+    fn assert_whole_bytes(&self) {
+        assert!(
+            self.extra_bits == 0,
+            "data added after a partial byte from update_bits()"
+        );
+    }
+
+    /// Adds each part to the given hash in order, as if they had been concatenated. Same as
+    /// calling `Sha1::update()` on every part.
+    pub fn update_iter<I>(&mut self, parts: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        for part in parts {
+            self.update(part);
+        }
+    }
+
+    /// Adds data from the front of `data` to the given hash, doing the hashing work for at most
+    /// `max_blocks` 64 byte blocks, and advances `data` past what was added. Returns the number
+    /// of bytes added, which is all of `data` once there's too little left to fill that many
+    /// blocks.
+    ///
+    /// This is for hashing large buffers a little at a time, for example in a single-threaded
+    /// event loop that must yield regularly.
+    ///
+    /// Panics if called after `Sha1::update_bits()` added a partial byte.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use sha1::Sha1;
+    /// let buffer = vec![0; 1 << 20];
+    /// let mut data = &buffer[..];
+    /// let mut s = Sha1::new();
+    /// while !data.is_empty() {
+    ///     s.process_budget(&mut data, 256);
+    ///     // Let other tasks run...
+    /// }
+    /// assert_eq!(s.finish(), Sha1::digest(&buffer));
+    /// ```
+    pub fn process_budget(&mut self, data: &mut &[u8], max_blocks: usize) -> usize {
+        // update() processes a block as soon as it's full, so this takes everything up to the
+        // byte that would fill block max_blocks + 1
+        let limit = max_blocks
+            .saturating_mul(64)
+            .saturating_add(63 - self.used as usize);
+        let n = data.len().min(limit);
+
+        self.update(&data[..n]);
+        *data = &data[n..];
+        n
+    }
+
+    /// Adds a string to the given hash encoded as UTF-16LE. The string is transcoded in small
+    /// pieces as it is hashed, without encoding all of it first.
+    pub fn update_utf16le(&mut self, s: &str) {
+        let mut buf = [0; 64];
+        let mut len = 0;
+
+        for unit in s.encode_utf16() {
+            buf[len..len + 2].copy_from_slice(&unit.to_le_bytes());
+            len += 2;
+            if len == buf.len() {
+                self.update(buf);
+                len = 0;
+            }
+        }
+
+        self.update(&buf[..len]);
+    }
+
+    /// Adds all remaining data in a `bytes::Buf` to the given hash, one contiguous chunk at a
+    /// time, so chained or rope-style buffers don't need to be copied into one slice first.
+    #[cfg(feature = "bytes")]
+    pub fn update_buf<B: bytes::Buf>(&mut self, mut buf: B) {
+        while buf.has_remaining() {
+            let chunk = buf.chunk();
+            let len = chunk.len();
+            self.update(chunk);
+            buf.advance(len);
+        }
+    }
+
     /// Finishes all work for a given hash and returns the final result.
-    /// Using a "finished" `Sha1` struct without calling `Sha1::reset()` will
-    /// produce incorrect hashes.
-    pub fn finish(&mut self) -> Hash {
+    ///
+    /// Consumes the `Sha1` struct, since adding more data to a finished hash would produce
+    /// incorrect results. Clone it first to keep hashing data with the same prefix.
+    ///
+    /// Panics if more than 2^64 - 1 bits (about 2 EiB) were hashed, which SHA-1 does not
+    /// support. See `Sha1::try_finish()`.
+    pub fn finish(mut self) -> Hash {
+        self.finalize()
+    }
+
+    /// Same as `Sha1::finish()`, but returns `Sha1Error::LengthOverflow` instead of panicking
+    /// if too much data was hashed.
+    pub fn try_finish(mut self) -> Result<Hash, Sha1Error> {
+        self.message_length().ok_or(Sha1Error::LengthOverflow)?;
+        Ok(self.finalize())
+    }
+
+    // Returns the length of all data added so far in bits, or None if it doesn't fit in a u64.
+    fn message_length(&self) -> Option<u64> {
+        self.chunks_processed
+            .checked_mul(512)?
+            .checked_add(8 * self.used as u64 + self.extra_bits as u64)
+    }
+
+    /// Finishes all work for a given hash, returns the final result, and resets the `Sha1`
+    /// struct so it can be used for the next hash.
+    pub fn finish_reset(&mut self) -> Hash {
+        let hash = self.finalize();
+        self.reset();
+        hash
+    }
+
+    /// Returns the hash of all data added so far without finishing the `Sha1` struct, so more
+    /// data can still be added afterwards.
+    pub fn peek_finish(&self) -> Hash {
+        self.clone().finalize()
+    }
+
+    // Pads and processes the final chunk. Leaves self in a state that produces incorrect hashes
+    // until it is reset. Panics if the message length overflows.
+    fn finalize(&mut self) -> Hash {
         // To finalize the hash, we need to add at least 9 bytes to the next chunk. The 0x80 byte
         // at the end of the message data, and an 8 byte message length.
-        let message_length: u64 = self.chunks_processed * 512 + 8 * self.used as u64;
+        let message_length = self
+            .message_length()
+            .expect("message is longer than SHA-1's limit of 2^64 - 1 bits");
 
-        // Add byte 10000000
-        self.chunk[self.used as usize] = 0x80;
+        // Add byte 10000000, or just its 1 bit after a partial byte from update_bits()
+        if self.extra_bits == 0 {
+            self.chunk[self.used as usize] = 0x80;
+        } else {
+            self.chunk[self.used as usize] |= 0x80 >> self.extra_bits;
+            self.extra_bits = 0;
+        }
         self.used += 1;
 
         if self.used <= 56 {
@@ -193,99 +709,212 @@ impl Sha1 {
         [self.h0, self.h1, self.h2, self.h3, self.h4]
     }
 
-    fn process_chunk(&mut self) {
-        // Increment chunks_processed, used to compute total message length in finish()
-        self.chunks_processed += 1;
+    /// Returns the number of bytes added to the hash so far. A partial byte from
+    /// `Sha1::update_bits()` is not counted. After `Sha1::from_digest()`, this includes the
+    /// original message and its padding.
+    pub fn bytes_processed(&self) -> u64 {
+        self.chunks_processed * 64 + self.used as u64
+    }
 
-        // 80 word buffer
-        let mut w = [0u32; 80];
+    /// Returns the number of bytes buffered in the internal chunk, waiting for the chunk to be
+    /// filled before they are processed. Always less than 64.
+    pub fn buffered_len(&self) -> usize {
+        self.used as usize
+    }
 
-        // Fill first 16 words with data from self.chunk
-        for i in 0..16 {
-            let word = self.chunk[i * 4..(i + 1) * 4].try_into().unwrap();
-            w[i] = u32::from_be_bytes(word);
-        }
+    /// Returns true if no data has been added since the `Sha1` struct was created or reset.
+    pub fn is_fresh(&self) -> bool {
+        self.chunks_processed == 0 && self.used == 0 && self.extra_bits == 0
+    }
 
-        // Extend to 80 words using data from first 16
-        for i in 16..32 {
-            w[i] = w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16];
-            w[i] = leftrotate(w[i], 1);
-        }
+    /// Length in bytes of the state produced by `Sha1::export_state()`.
+    pub const STATE_LEN: usize = 95;
 
-        // Although having the above loop go from 16..80 works,
-        // This alternative makes the extension process
-        // slightly faster on x86
-        for i in 32..80 {
-            w[i] = w[i - 6] ^ w[i - 16] ^ w[i - 28] ^ w[i - 32];
-            w[i] = leftrotate(w[i], 2);
-        }
+    // Version of the layout of exported states, stored in their first byte
+    const STATE_VERSION: u8 = 1;
 
-        // Initialize hash value for this chunk
-        let mut a = self.h0;
-        let mut b = self.h1;
-        let mut c = self.h2;
-        let mut d = self.h3;
-        let mut e = self.h4;
-
-        // Using wrapping_add instead of + prevents overflow panic in debug mode
-        // but also produces equivalent code to + in release mode.
-        macro_rules! shuffle {
-            ($w:expr, $f:expr, $k:expr) => {
-                let tmp = leftrotate(a, 5)
-                    .wrapping_add($f)
-                    .wrapping_add(e)
-                    .wrapping_add($k)
-                    .wrapping_add($w);
-                e = d;
-                d = c;
-                c = leftrotate(b, 30);
-                b = a;
-                a = tmp;
-            };
+    /// Exports the complete state of an in-progress hash so that it can be saved and later
+    /// resumed with `Sha1::import_state()`, possibly in another process.
+    ///
+    /// The state is laid out as a version byte, the five big-endian hash value words, the
+    /// big-endian count of processed chunks, the number of buffered bytes, the 64 byte chunk
+    /// buffer (with unused bytes zeroed), and the number of bits in a partial byte from
+    /// `Sha1::update_bits()`. It contains the buffered input data in the clear.
+    pub fn export_state(&self) -> [u8; Sha1::STATE_LEN] {
+        let mut state = [0; Sha1::STATE_LEN];
+        let used = self.used as usize + (self.extra_bits > 0) as usize;
+
+        state[0] = Sha1::STATE_VERSION;
+        for (i, h) in [self.h0, self.h1, self.h2, self.h3, self.h4]
+            .iter()
+            .enumerate()
+        {
+            state[1 + i * 4..1 + (i + 1) * 4].copy_from_slice(&h.to_be_bytes());
         }
 
-        // Do some hashing...
-        for &w in &w[0..20] {
-            let f = (b & c) | ((!b) & d);
-            shuffle!(w, f, 0x5A827999);
-        }
+        state[21..29].copy_from_slice(&self.chunks_processed.to_be_bytes());
+        state[29] = self.used;
+        state[30..30 + used].copy_from_slice(&self.chunk[..used]);
+        state[94] = self.extra_bits;
+        state
+    }
 
-        for &w in &w[20..40] {
-            let f = b ^ c ^ d;
-            shuffle!(w, f, 0x6ED9EBA1);
+    /// Restores a hash from a state produced by `Sha1::export_state()`. Returns
+    /// `Sha1Error::InvalidState` if the state is invalid or from an unknown version of the
+    /// layout.
+    pub fn import_state(state: &[u8; Sha1::STATE_LEN]) -> Result<Sha1, Sha1Error> {
+        let word =
+            |i: usize| u32::from_be_bytes(state[1 + i * 4..1 + (i + 1) * 4].try_into().unwrap());
+        let used = state[29];
+        let extra_bits = state[94];
+
+        // The chunk buffer is never left full
+        if state[0] != Sha1::STATE_VERSION || used >= 64 || extra_bits >= 8 {
+            return Err(Sha1Error::InvalidState);
         }
 
-        for &w in &w[40..60] {
-            let f = (b & c) | (b & d) | (c & d);
-            shuffle!(w, f, 0x8F1BBCDC);
-        }
+        let mut chunk = [0; 64];
+        chunk.copy_from_slice(&state[30..94]);
+
+        Ok(Sha1 {
+            chunk,
+            used,
+            extra_bits,
+            chunks_processed: u64::from_be_bytes(state[21..29].try_into().unwrap()),
+            h0: word(0),
+            h1: word(1),
+            h2: word(2),
+            h3: word(3),
+            h4: word(4),
+        })
+    }
+
+    fn process_chunk(&mut self) {
+        // Increment chunks_processed, used to compute total message length in finalize()
+        self.chunks_processed += 1;
+
+        let mut h = [self.h0, self.h1, self.h2, self.h3, self.h4];
+        compress(&mut h, &self.chunk);
+
+        self.h0 = h[0];
+        self.h1 = h[1];
+        self.h2 = h[2];
+        self.h3 = h[3];
+        self.h4 = h[4];
+    }
+}
 
-        for &w in &w[60..80] {
-            let f = b ^ c ^ d;
-            shuffle!(w, f, 0xCA62C1D6);
+impl fmt::Debug for Sha1 {
+    /// Shows only how much has been hashed, not the hashed data or the hash value.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sha1")
+            .field("bytes_processed", &self.bytes_processed())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Sha1 {
+    /// Equivalent to `Sha1::new()`.
+    fn default() -> Sha1 {
+        Sha1::new()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Sha1 {
+    /// Zeroes all internal state, including buffered data. The `Sha1` struct must be reset
+    /// before it is used again.
+    fn zeroize(&mut self) {
+        self.chunk.zeroize();
+        self.used.zeroize();
+        self.extra_bits.zeroize();
+        self.chunks_processed.zeroize();
+        self.h0.zeroize();
+        self.h1.zeroize();
+        self.h2.zeroize();
+        self.h3.zeroize();
+        self.h4.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Sha1 {
+    /// Zeroes all internal state, so hashed data does not linger in memory.
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Sha1 {}
+
+impl Extend<u8> for Sha1 {
+    /// Adds every byte from an iterator to the hash, filling the internal chunk directly rather
+    /// than collecting the bytes first.
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.assert_whole_bytes();
+        let mut used = self.used as usize;
+
+        for byte in iter {
+            self.chunk[used] = byte;
+            used += 1;
+
+            if used == 64 {
+                self.process_chunk();
+                used = 0;
+            }
         }
 
-        self.h0 = self.h0.wrapping_add(a);
-        self.h1 = self.h1.wrapping_add(b);
-        self.h2 = self.h2.wrapping_add(c);
-        self.h3 = self.h3.wrapping_add(d);
-        self.h4 = self.h4.wrapping_add(e);
+        self.used = used as u8;
+    }
+}
+
+impl<'a> Extend<&'a u8> for Sha1 {
+    /// Adds every byte from an iterator to the hash. See `Extend<u8>`.
+    fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
     }
 }
 
-impl Write for Sha1 {
-    /// Writes all data to hasher by calling `self.update(data)` and returns `Ok(data.len())`.
-    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-        self.update(data);
-        Ok(data.len())
+impl FromIterator<u8> for Sha1 {
+    /// Creates a new `Sha1` struct and adds every byte from an iterator to it.
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Sha1 {
+        let mut s = Sha1::new();
+        s.extend(iter);
+        s
+    }
+}
+
+impl<'a> FromIterator<&'a u8> for Sha1 {
+    /// Creates a new `Sha1` struct and adds every byte from an iterator to it.
+    fn from_iter<I: IntoIterator<Item = &'a u8>>(iter: I) -> Sha1 {
+        let mut s = Sha1::new();
+        s.extend(iter);
+        s
     }
+}
 
-    /// Does nothing and returns `Ok(())`. There is nothing that flush would make sense to do.
-    fn flush(&mut self) -> io::Result<()> {
+impl fmt::Write for Sha1 {
+    /// Hashes the UTF-8 bytes of `s`, allowing formatted data to be hashed with `write!()`
+    /// without building an intermediate `String`. Always returns `Ok(())`.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.update(s);
         Ok(())
     }
 }
 
-fn leftrotate(word: u32, bits: u8) -> u32 {
-    (word << bits) | (word >> (32 - bits))
+// Length of the padding appended to a message_len byte message: the 0x80 byte and 8 length bytes,
+// plus enough zeroes to end on a chunk boundary.
+const fn padding_len(message_len: u64) -> u64 {
+    9 + (64 - message_len.wrapping_add(9) % 64) % 64
+}
+
+// Converts hash words to big-endian bytes.
+pub(crate) fn hash_to_bytes(hash: &Hash) -> [u8; 20] {
+    let mut bytes = [0; 20];
+    for (i, word) in hash.iter().enumerate() {
+        bytes[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
+    }
+
+    bytes
 }
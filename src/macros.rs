@@ -0,0 +1,33 @@
+/// Hashes a string literal at compile time and expands to its hash as a `[u8; 20]`.
+///
+/// Example usage:
+/// ```
+/// const HELLO: [u8; 20] = sha1::sha1!("Hello, world");
+/// assert_eq!(sha1::Digest::from(HELLO), "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8");
+/// ```
+#[macro_export]
+macro_rules! sha1 {
+    ($s:expr) => {{
+        const HASH: [u8; 20] = $crate::Sha1::digest_const(str::as_bytes($s));
+        HASH
+    }};
+}
+
+/// Hashes a file at compile time and expands to its hash as a `[u8; 20]`. The path is resolved
+/// like `include_bytes!()`, relative to the file the macro is used in, and the file is embedded
+/// in the build only while it is hashed. Large files work, but add to compile time: a 256 KB file
+/// takes a few seconds.
+///
+/// Example usage:
+/// ```ignore
+/// const LOGO: [u8; 20] = sha1::sha1_file!("../assets/logo.png");
+/// ```
+#[macro_export]
+macro_rules! sha1_file {
+    ($path:expr) => {{
+        // Files over about 100 KB take longer than rustc's const evaluation limit allows
+        #[allow(long_running_const_eval)]
+        const HASH: [u8; 20] = $crate::Sha1::digest_const(include_bytes!($path));
+        HASH
+    }};
+}
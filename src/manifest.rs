@@ -0,0 +1,295 @@
+//! Reading, writing, and verifying checksum files like those produced by `sha1sum`.
+//!
+//! Both GNU style (`<hex>  <path>`) and BSD style (`SHA1 (<path>) = <hex>`) lines are supported.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::manifest::{Manifest, Status};
+//! # use std::fs::File;
+//! # use std::io::BufReader;
+//! # fn f() -> std::io::Result<()> {
+//! // Check every file listed in a checksum file
+//! let manifest = Manifest::read(BufReader::new(File::open("foo.sha1")?))?;
+//! for (entry, status) in manifest.verify(".") {
+//!     if status != Status::Ok {
+//!         println!("{}: {:?}", entry.path, status);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{hex, Hash, Sha1Error};
+use std::fmt;
+use std::io::{BufRead, Write};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use crate::Sha1;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::path::Path;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::mpsc;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::thread;
+
+/// Style of a checksum line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// `<hex>  <path>`
+    Gnu,
+    /// `<hex> *<path>`, GNU style with the file marked as binary
+    GnuBinary,
+    /// `SHA1 (<path>) = <hex>`
+    Bsd,
+}
+
+/// One line of a checksum file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub hash: Hash,
+    pub path: String,
+    pub format: Format,
+}
+
+/// Result of verifying one entry.
+#[derive(Debug)]
+pub enum Status {
+    /// The file's hash matches.
+    Ok,
+    /// The file's hash is different. Contains the actual hash.
+    Mismatch(Hash),
+    /// The file does not exist.
+    Missing,
+    /// The file could not be read.
+    Error(Sha1Error),
+}
+
+impl PartialEq for Status {
+    /// Compares statuses. I/O errors are compared only by their kind.
+    fn eq(&self, other: &Status) -> bool {
+        match (self, other) {
+            (Status::Ok, Status::Ok) | (Status::Missing, Status::Missing) => true,
+            (Status::Mismatch(a), Status::Mismatch(b)) => a == b,
+            (Status::Error(a), Status::Error(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Number of entries with each status, from `Manifest::verify_parallel()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub ok: usize,
+    pub mismatched: usize,
+    pub missing: usize,
+    pub errors: usize,
+}
+
+impl Summary {
+    /// Returns the number of entries verified.
+    pub fn total(&self) -> usize {
+        self.ok + self.mismatched + self.missing + self.errors
+    }
+
+    /// Returns whether every entry verified was `Status::Ok`.
+    pub fn all_ok(&self) -> bool {
+        self.ok == self.total()
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn add(&mut self, status: &Status) {
+        match status {
+            Status::Ok => self.ok += 1,
+            Status::Mismatch(_) => self.mismatched += 1,
+            Status::Missing => self.missing += 1,
+            Status::Error(_) => self.errors += 1,
+        }
+    }
+}
+
+impl Entry {
+    /// Creates an entry by hashing the file at `path`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn from_path(path: &str, format: Format) -> Result<Entry, Sha1Error> {
+        let (hash, _) = Sha1::digest_path(path)?;
+
+        Ok(Entry {
+            hash,
+            path: path.to_string(),
+            format,
+        })
+    }
+
+    /// Parses a single checksum line in any supported format. Returns `None` if the line is not
+    /// properly formatted.
+    pub fn parse(line: &str) -> Option<Entry> {
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        if let Some(rest) = line.strip_prefix("SHA1 (") {
+            let split = rest.rfind(") = ")?;
+
+            return Some(Entry {
+                hash: hex::decode(&rest[split + 4..]).ok()?,
+                path: rest[..split].to_string(),
+                format: Format::Bsd,
+            });
+        }
+
+        if line.len() < 42 || !line.is_char_boundary(40) {
+            return None;
+        }
+
+        let (hash, rest) = line.split_at(40);
+        let format = match rest.as_bytes()[..2] {
+            [b' ', b' '] => Format::Gnu,
+            [b' ', b'*'] => Format::GnuBinary,
+            _ => return None,
+        };
+
+        Some(Entry {
+            hash: hex::decode(hash).ok()?,
+            path: rest[2..].to_string(),
+            format,
+        })
+    }
+
+    /// Hashes the file at `self.path`, relative to `base`, and compares it to `self.hash`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn verify<P: AsRef<Path>>(&self, base: P) -> Status {
+        match Sha1::digest_path(base.as_ref().join(&self.path)) {
+            Ok((hash, _)) if hash == self.hash => Status::Ok,
+            Ok((hash, _)) => Status::Mismatch(hash),
+            Err(Sha1Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Status::Missing,
+            Err(e) => Status::Error(e),
+        }
+    }
+}
+
+impl fmt::Display for Entry {
+    /// Formats the entry as a checksum line in its format, without a line ending.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hash = hex::encode(&self.hash);
+
+        match self.format {
+            Format::Gnu => write!(f, "{}  {}", hash, self.path),
+            Format::GnuBinary => write!(f, "{} *{}", hash, self.path),
+            Format::Bsd => write!(f, "SHA1 ({}) = {}", self.path, hash),
+        }
+    }
+}
+
+/// Contents of a checksum file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: Vec<Entry>,
+
+    /// Line numbers, starting at 1, of lines that were not properly formatted.
+    pub bad_lines: Vec<usize>,
+}
+
+impl Manifest {
+    /// Reads every line of a checksum file. Blank lines are ignored, and improperly formatted
+    /// lines are recorded in `bad_lines`.
+    pub fn read<R: BufRead>(reader: R) -> Result<Manifest, Sha1Error> {
+        let mut manifest = Manifest::default();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match Entry::parse(&line) {
+                Some(entry) => manifest.entries.push(entry),
+                None => manifest.bad_lines.push(i + 1),
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    /// Writes every entry as a checksum line.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), Sha1Error> {
+        for entry in &self.entries {
+            writeln!(writer, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Verifies every entry, with paths relative to `base`, and returns each entry along with
+    /// its status.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn verify<P: AsRef<Path>>(&self, base: P) -> Vec<(&Entry, Status)> {
+        let base = base.as_ref();
+        self.entries.iter().map(|e| (e, e.verify(base))).collect()
+    }
+
+    /// Verifies every entry, with paths relative to `base`, on up to `threads` threads at once,
+    /// and returns how many entries had each status.
+    ///
+    /// `on_result` is called on the calling thread with each entry and its status as soon as it
+    /// is verified, so entries are reported in the order they finish rather than manifest order.
+    /// If `threads` is 0, one thread per CPU is used.
+    ///
+    /// Example usage:
+    /// ```
+    /// # use sha1::manifest::{Manifest, Status};
+    /// # fn f(manifest: Manifest) {
+    /// let summary = manifest.verify_parallel(".", 8, |entry, status| {
+    ///     if *status != Status::Ok {
+    ///         println!("{}: {:?}", entry.path, status);
+    ///     }
+    /// });
+    /// println!("{} of {} files ok", summary.ok, summary.total());
+    /// # }
+    /// ```
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn verify_parallel<P, F>(&self, base: P, threads: usize, mut on_result: F) -> Summary
+    where
+        P: AsRef<Path>,
+        F: FnMut(&Entry, &Status),
+    {
+        let base = base.as_ref();
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        // Each worker takes the next unclaimed entry until there are none left, and sends back
+        // its index with the status
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        let mut summary = Summary::default();
+
+        thread::scope(|s| {
+            for _ in 0..threads.min(self.entries.len()) {
+                let sender = sender.clone();
+                let next = &next;
+
+                s.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let entry = match self.entries.get(i) {
+                        Some(entry) => entry,
+                        None => break,
+                    };
+                    if sender.send((i, entry.verify(base))).is_err() {
+                        break;
+                    }
+                });
+            }
+
+            // Once every worker has finished, the last sender is gone and the loop ends
+            drop(sender);
+            for (i, status) in receiver {
+                summary.add(&status);
+                on_result(&self.entries[i], &status);
+            }
+        });
+
+        summary
+    }
+}
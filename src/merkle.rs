@@ -0,0 +1,173 @@
+//! SHA-1 Merkle trees over fixed-size leaves, with inclusion proofs.
+//!
+//! Leaves and inner nodes are hashed with different prefix bytes, like RFC 6962, so that a leaf
+//! can never be passed off as an inner node. A node without a sibling is moved up to the next
+//! level unchanged.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::merkle::MerkleTree;
+//! # fn f() -> std::io::Result<()> {
+//! let data = vec![7; 10_000];
+//! let tree = MerkleTree::from_reader(&data[..], 1024)?;
+//!
+//! // Prove that the fourth leaf is part of the tree
+//! let proof = tree.proof(3).unwrap();
+//! assert!(proof.verify(&data[3072..4096], &tree.root()));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::pieces::read_full;
+use crate::{hash_to_bytes, Hash, Sha1, Sha1Error};
+use std::io::Read;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::{fs::File, path::Path};
+
+/// Returns the hash of one leaf of data: SHA-1 of a 0x00 byte followed by the data.
+pub fn leaf_hash<D: AsRef<[u8]>>(data: D) -> Hash {
+    let mut s = Sha1::new();
+    s.update([0x00]);
+    s.update(data);
+    s.finish()
+}
+
+/// Returns the hash of an inner node: SHA-1 of a 0x01 byte followed by both child hashes.
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut s = Sha1::new();
+    s.update([0x01]);
+    s.update(hash_to_bytes(left));
+    s.update(hash_to_bytes(right));
+    s.finish()
+}
+
+/// A Merkle tree with every level kept in memory, so that proofs can be made for any leaf.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    // levels[0] holds the leaf hashes, the last level holds only the root
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// Builds a tree from everything in a reader, split into leaves of `leaf_len` bytes. The last
+    /// leaf is shorter if the total length is not a multiple of `leaf_len`.
+    ///
+    /// Panics if `leaf_len` is 0.
+    pub fn from_reader<R: Read>(mut reader: R, leaf_len: usize) -> Result<MerkleTree, Sha1Error> {
+        assert!(leaf_len > 0, "leaf length must not be 0");
+
+        let mut buf = vec![0; leaf_len];
+        let mut leaves = Vec::new();
+
+        loop {
+            let n = read_full(&mut reader, &mut buf)?;
+            if n > 0 {
+                leaves.push(leaf_hash(&buf[..n]));
+            }
+
+            // A short read means the reader is exhausted
+            if n < buf.len() {
+                return Ok(MerkleTree::from_leaves(leaves));
+            }
+        }
+    }
+
+    /// Builds a tree from a whole file by its path. See
+    /// [`from_reader`](#method.from_reader).
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn from_path<P>(path: P, leaf_len: usize) -> Result<MerkleTree, Sha1Error>
+    where
+        P: AsRef<Path>,
+    {
+        MerkleTree::from_reader(File::open(path)?, leaf_len)
+    }
+
+    /// Builds a tree from leaf hashes made with [`leaf_hash`](fn.leaf_hash.html).
+    pub fn from_leaves(leaves: Vec<Hash>) -> MerkleTree {
+        let mut levels = vec![leaves];
+
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap();
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        MerkleTree { levels }
+    }
+
+    /// Returns the root hash. The root of a tree with no leaves is the SHA-1 of no data.
+    pub fn root(&self) -> Hash {
+        match self.levels.last().unwrap().first() {
+            Some(root) => *root,
+            None => Sha1::digest([]),
+        }
+    }
+
+    /// Returns the leaf hashes, in order.
+    pub fn leaves(&self) -> &[Hash] {
+        &self.levels[0]
+    }
+
+    /// Returns an inclusion proof for the leaf at `index`, or `None` if there is no such leaf.
+    pub fn proof(&self, index: usize) -> Option<Proof> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut i = index;
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            if i % 2 == 1 {
+                path.push((Side::Left, level[i - 1]));
+            } else if i + 1 < level.len() {
+                path.push((Side::Right, level[i + 1]));
+            }
+            i /= 2;
+        }
+
+        Some(Proof { path })
+    }
+}
+
+/// Which side of the path a sibling hash in a `Proof` is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Inclusion proof for one leaf: the sibling hashes on the path from the leaf to the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof {
+    /// Sibling hashes from the bottom of the tree up, with the side each one is on.
+    pub path: Vec<(Side, Hash)>,
+}
+
+impl Proof {
+    /// Returns true if `leaf` is the data of the proven leaf in the tree with the given root.
+    pub fn verify<D: AsRef<[u8]>>(&self, leaf: D, root: &Hash) -> bool {
+        self.verify_hash(&leaf_hash(leaf), root)
+    }
+
+    /// Same as `Proof::verify()`, but takes the hash of the leaf instead of its data.
+    pub fn verify_hash(&self, leaf: &Hash, root: &Hash) -> bool {
+        let computed = self
+            .path
+            .iter()
+            .fold(*leaf, |hash, (side, sibling)| match side {
+                Side::Left => node_hash(sibling, &hash),
+                Side::Right => node_hash(&hash, sibling),
+            });
+
+        computed == *root
+    }
+}
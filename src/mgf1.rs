@@ -0,0 +1,50 @@
+//! The MGF1 mask generation function from PKCS #1 (RFC 8017), with SHA-1 as the hash, as used by
+//! RSA-OAEP and RSA-PSS with SHA-1 parameters.
+//!
+//! The mask is the concatenation of the hashes of the seed followed by a 32 bit big-endian
+//! counter starting at 0, cut to the requested length.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::mgf1::mgf1_sha1;
+//! let mask = mgf1_sha1(b"foo", 5);
+//! assert_eq!(mask, [0x1a, 0xc9, 0x07, 0x5c, 0xd4]);
+//! ```
+
+use crate::{hash_to_bytes, Sha1};
+
+/// Returns `out_len` bytes of mask generated from `seed`.
+///
+/// Panics if `out_len` is more than 2^32 hashes long, which is the limit set by RFC 8017.
+pub fn mgf1_sha1<S: AsRef<[u8]>>(seed: S, out_len: usize) -> Vec<u8> {
+    let mut out = vec![0; out_len];
+    mgf1_sha1_into(seed, &mut out);
+    out
+}
+
+/// Same as `mgf1_sha1()`, but fills `out` with the mask instead of returning it.
+pub fn mgf1_sha1_into<S: AsRef<[u8]>>(seed: S, out: &mut [u8]) {
+    assert!(
+        out.len() as u64 <= 20 << 32,
+        "mask is longer than 2^32 hashes"
+    );
+
+    // The seed is the same for every block
+    let mut prefix = Sha1::new();
+    prefix.update(seed);
+
+    for (i, block) in out.chunks_mut(20).enumerate() {
+        let mut s = prefix.clone();
+        s.update((i as u32).to_be_bytes());
+        block.copy_from_slice(&hash_to_bytes(&s.finish())[..block.len()]);
+    }
+}
+
+/// XORs the mask generated from `seed` into `data`, as done to the data block and seed in
+/// RSA-OAEP and to the data block in RSA-PSS.
+pub fn mgf1_sha1_xor<S: AsRef<[u8]>>(seed: S, data: &mut [u8]) {
+    let mask = mgf1_sha1(seed, data.len());
+    for (d, m) in data.iter_mut().zip(mask) {
+        *d ^= m;
+    }
+}
@@ -0,0 +1,83 @@
+//! OAuth 1.0a HMAC-SHA1 request signing (RFC 5849).
+//!
+//! Example usage:
+//! ```
+//! # use sha1::oauth1;
+//! # fn f(consumer_secret: &str, token_secret: &str) {
+//! let params = [
+//!     ("status", "Hello, world"),
+//!     ("oauth_consumer_key", "key"),
+//!     ("oauth_nonce", "nonce"),
+//!     ("oauth_signature_method", "HMAC-SHA1"),
+//!     ("oauth_timestamp", "1318622958"),
+//!     ("oauth_token", "token"),
+//!     ("oauth_version", "1.0"),
+//! ];
+//! let url = "https://api.example.com/1/statuses/update.json";
+//! let signature = oauth1::signature("POST", url, &params, consumer_secret, token_secret);
+//!
+//! // The Authorization header requires the signature to be percent-encoded
+//! let header_value = oauth1::percent_encode(&signature);
+//! # }
+//! ```
+
+use crate::hmac::Hmac;
+use crate::{base64, hash_to_bytes};
+
+/// Computes the base64 encoded HMAC-SHA1 signature of a request.
+///
+/// `url` is the base URL of the request, without its query string or fragment. `params` are all
+/// request parameters, including query string parameters, form body parameters, and `oauth_*`
+/// parameters other than `oauth_signature`. They are not percent-encoded and may be in any order.
+/// `token_secret` is empty when there is no token yet.
+pub fn signature(
+    method: &str,
+    url: &str,
+    params: &[(&str, &str)],
+    consumer_secret: &str,
+    token_secret: &str,
+) -> String {
+    let key = format!(
+        "{}&{}",
+        percent_encode(consumer_secret),
+        percent_encode(token_secret)
+    );
+    let base = base_string(method, url, params);
+    base64::encode(&hash_to_bytes(&Hmac::mac(key, base)))
+}
+
+/// Builds the signature base string of a request, as described in RFC 5849 section 3.4.1.
+pub fn base_string(method: &str, url: &str, params: &[(&str, &str)]) -> String {
+    // Parameters are sorted by name, then value, after they are encoded
+    let mut params: Vec<(String, String)> = params
+        .iter()
+        .map(|&(k, v)| (percent_encode(k), percent_encode(v)))
+        .collect();
+    params.sort();
+
+    let params: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+
+    format!(
+        "{}&{}&{}",
+        method.to_ascii_uppercase(),
+        percent_encode(url),
+        percent_encode(&params.join("&"))
+    )
+}
+
+/// Percent-encodes a string as required by OAuth: every byte of its UTF-8 encoding except
+/// unreserved characters (`A-Z a-z 0-9 - . _ ~`) is encoded as `%XX` with uppercase hex digits.
+pub fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+
+    out
+}
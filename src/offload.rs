@@ -0,0 +1,112 @@
+use crate::{Hash, Sha1};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+/// Hashes data on a background thread, so that the thread producing the data only has to hand
+/// over buffers.
+///
+/// Buffers are queued in a bounded channel. When the queue is full, `update()` blocks until the
+/// worker catches up, and `try_update()` gives the buffer back instead.
+///
+/// Example usage:
+/// ```
+/// # use sha1::OffloadHasher;
+/// let hasher = OffloadHasher::new();
+/// hasher.update(b"First part of hashed data".to_vec());
+/// hasher.update(b"Second part of hashed data".to_vec());
+///
+/// // Do other work while the last buffers are hashed, then wait for the result
+/// let handle = hasher.close();
+/// let hash = handle.wait();
+/// ```
+pub struct OffloadHasher {
+    sender: SyncSender<Vec<u8>>,
+    worker: JoinHandle<Hash>,
+}
+
+impl OffloadHasher {
+    /// Number of buffers that can be queued unless another bound is set with
+    /// `OffloadHasher::with_capacity()`.
+    pub const DEFAULT_CAPACITY: usize = 16;
+
+    /// Creates a new `OffloadHasher` and starts its worker thread.
+    pub fn new() -> OffloadHasher {
+        OffloadHasher::with_capacity(OffloadHasher::DEFAULT_CAPACITY)
+    }
+
+    /// Creates a new `OffloadHasher` that queues up to `capacity` buffers, and starts its worker
+    /// thread.
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn with_capacity(capacity: usize) -> OffloadHasher {
+        assert!(capacity > 0, "capacity must not be 0");
+
+        let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(capacity);
+        let worker = thread::spawn(move || {
+            let mut s = Sha1::new();
+            for buf in receiver {
+                s.update(buf);
+            }
+            s.finish()
+        });
+
+        OffloadHasher { sender, worker }
+    }
+
+    /// Queues a buffer to be added to the hash, blocking while the queue is full.
+    pub fn update(&self, buf: Vec<u8>) {
+        // The worker only stops receiving once the sender is dropped
+        self.sender.send(buf).expect("hashing thread stopped");
+    }
+
+    /// Queues a buffer to be added to the hash if there is room in the queue. Otherwise returns
+    /// the buffer.
+    pub fn try_update(&self, buf: Vec<u8>) -> Result<(), Vec<u8>> {
+        match self.sender.try_send(buf) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(buf)) => Err(buf),
+            Err(TrySendError::Disconnected(_)) => panic!("hashing thread stopped"),
+        }
+    }
+
+    /// Stops accepting data and returns a handle to the hash, which is ready once the worker
+    /// thread has hashed every queued buffer.
+    pub fn close(self) -> OffloadHandle {
+        drop(self.sender);
+        OffloadHandle {
+            worker: self.worker,
+        }
+    }
+
+    /// Stops accepting data and waits for the hash. Same as `self.close().wait()`.
+    pub fn finish(self) -> Hash {
+        self.close().wait()
+    }
+}
+
+impl Default for OffloadHasher {
+    /// Equivalent to `OffloadHasher::new()`.
+    fn default() -> OffloadHasher {
+        OffloadHasher::new()
+    }
+}
+
+/// Handle to the result of an `OffloadHasher`, returned by `OffloadHasher::close()`.
+pub struct OffloadHandle {
+    worker: JoinHandle<Hash>,
+}
+
+impl OffloadHandle {
+    /// Returns true if the hash is ready, so that `wait()` won't block.
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Waits for the worker thread to hash every queued buffer and returns the hash.
+    pub fn wait(self) -> Hash {
+        match self.worker.join() {
+            Ok(hash) => hash,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
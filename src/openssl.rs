@@ -0,0 +1,150 @@
+//! SHA-1 computed by OpenSSL's libcrypto, enabled by the `openssl` feature, for deployments that
+//! must hash with the system's validated crypto module rather than an in-process implementation.
+//!
+//! Hashing goes through OpenSSL's EVP interface, so it uses whichever provider OpenSSL is
+//! configured with, including the FIPS provider. It doesn't depend on `Sha1::active_backend()`,
+//! and `Sha1::force_portable()` and `SHA1_FORCE_SOFTWARE` don't affect it.
+//!
+//! EVP only hashes whole messages, so `OpenSslSha1` has none of `Sha1`'s midstate features:
+//! exported states, length extension, and `compress()` always use this crate's code.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::{openssl::OpenSslSha1, Sha1};
+//! # fn f() -> Result<(), sha1::Sha1Error> {
+//! let mut s = OpenSslSha1::new()?;
+//! s.update(b"Hello, ")?;
+//! s.update(b"world")?;
+//! assert_eq!(s.finish()?, Sha1::digest(b"Hello, world"));
+//! # Ok(())
+//! # }
+//! # f().unwrap();
+//! ```
+
+use crate::{Digest, Hash, Sha1Error};
+use std::io::{self, Write};
+use std::os::raw::{c_int, c_uint, c_ulong, c_void};
+use std::ptr::{self, NonNull};
+
+// EVP_MD_CTX and EVP_MD from openssl/evp.h, which are opaque
+#[repr(C)]
+struct EvpMdCtx {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct EvpMd {
+    _private: [u8; 0],
+}
+
+#[link(name = "crypto")]
+extern "C" {
+    fn EVP_MD_CTX_new() -> *mut EvpMdCtx;
+    fn EVP_MD_CTX_free(ctx: *mut EvpMdCtx);
+    fn EVP_MD_CTX_copy_ex(out: *mut EvpMdCtx, in_: *const EvpMdCtx) -> c_int;
+    fn EVP_sha1() -> *const EvpMd;
+    fn EVP_DigestInit_ex(ctx: *mut EvpMdCtx, md: *const EvpMd, engine: *mut c_void) -> c_int;
+    fn EVP_DigestUpdate(ctx: *mut EvpMdCtx, data: *const c_void, len: usize) -> c_int;
+    fn EVP_DigestFinal_ex(ctx: *mut EvpMdCtx, out: *mut u8, len: *mut c_uint) -> c_int;
+    fn ERR_get_error() -> c_ulong;
+}
+
+/// SHA-1 Hash context backed by an OpenSSL `EVP_MD_CTX`. Represents one single hash.
+///
+/// Every call returns `Sha1Error::OpenSsl` if libcrypto reports an error, such as when its
+/// configured provider doesn't allow SHA-1.
+pub struct OpenSslSha1 {
+    ctx: NonNull<EvpMdCtx>,
+}
+
+// Safety: an EVP_MD_CTX isn't tied to the thread that created it, and &self methods don't use it
+unsafe impl Send for OpenSslSha1 {}
+
+impl OpenSslSha1 {
+    /// Creates a new empty `OpenSslSha1` struct.
+    pub fn new() -> Result<OpenSslSha1, Sha1Error> {
+        let s = OpenSslSha1::alloc()?;
+
+        // Safety: ctx is a live context, and EVP_sha1() is a static digest description
+        check(unsafe { EVP_DigestInit_ex(s.ctx.as_ptr(), EVP_sha1(), ptr::null_mut()) })?;
+        Ok(s)
+    }
+
+    /// Utility function to hash data that is all available at once.
+    pub fn digest<D: AsRef<[u8]>>(data: D) -> Result<Hash, Sha1Error> {
+        let mut s = OpenSslSha1::new()?;
+        s.update(data)?;
+        s.finish()
+    }
+
+    /// Adds data to the hash.
+    pub fn update<D: AsRef<[u8]>>(&mut self, data: D) -> Result<(), Sha1Error> {
+        let data = data.as_ref();
+
+        // Safety: ctx is a live, initialized context, and data is valid for its length
+        check(unsafe { EVP_DigestUpdate(self.ctx.as_ptr(), data.as_ptr().cast(), data.len()) })
+    }
+
+    /// Finishes all work for the hash and returns the final result.
+    pub fn finish(self) -> Result<Hash, Sha1Error> {
+        let mut out = [0; 20];
+        let mut len = 0;
+
+        // Safety: ctx is a live, initialized context, and out has room for a SHA-1 digest
+        check(unsafe { EVP_DigestFinal_ex(self.ctx.as_ptr(), out.as_mut_ptr(), &mut len) })?;
+        debug_assert!(len == 20);
+        Ok(Digest::from(out).words())
+    }
+
+    /// Copies the hash, so that several messages sharing a common prefix can be hashed from it.
+    pub fn try_clone(&self) -> Result<OpenSslSha1, Sha1Error> {
+        let s = OpenSslSha1::alloc()?;
+
+        // Safety: both contexts are live, and only the new one is written to
+        check(unsafe { EVP_MD_CTX_copy_ex(s.ctx.as_ptr(), self.ctx.as_ptr()) })?;
+        Ok(s)
+    }
+
+    fn alloc() -> Result<OpenSslSha1, Sha1Error> {
+        // Safety: EVP_MD_CTX_new() has no preconditions, and returns null on failure
+        match NonNull::new(unsafe { EVP_MD_CTX_new() }) {
+            Some(ctx) => Ok(OpenSslSha1 { ctx }),
+            None => Err(last_error()),
+        }
+    }
+}
+
+impl Drop for OpenSslSha1 {
+    fn drop(&mut self) {
+        // Safety: ctx came from EVP_MD_CTX_new() and is freed only here
+        unsafe { EVP_MD_CTX_free(self.ctx.as_ptr()) };
+    }
+}
+
+impl Write for OpenSslSha1 {
+    /// Adds all data to the hash by calling `self.update(data)`. Errors from libcrypto are
+    /// returned as `io::ErrorKind::InvalidData` errors.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data)?;
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// EVP functions return 1 on success
+fn check(ret: c_int) -> Result<(), Sha1Error> {
+    if ret == 1 {
+        Ok(())
+    } else {
+        Err(last_error())
+    }
+}
+
+fn last_error() -> Sha1Error {
+    // Safety: ERR_get_error() only reads this thread's error queue
+    Sha1Error::OpenSsl(unsafe { ERR_get_error() } as u64)
+}
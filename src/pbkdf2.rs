@@ -0,0 +1,72 @@
+//! PBKDF2 key derivation with HMAC-SHA1 (RFC 8018 section 5.2), and the WPA2 pre-shared key
+//! derivation built on it.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::pbkdf2;
+//! // Derive a 32 byte key from a password
+//! let mut key = [0; 32];
+//! pbkdf2::derive(b"password", b"salt", 100_000, &mut key);
+//!
+//! // Derive the pairwise master key of a WPA2-Personal network
+//! let psk = pbkdf2::wpa2_psk("IEEE", "password");
+//! ```
+
+use crate::hash_to_bytes;
+use crate::hmac::Hmac;
+
+/// Fills `out` with key material derived from `password` and `salt` with `iterations` rounds of
+/// HMAC-SHA1.
+///
+/// Panics if `iterations` is 0.
+pub fn derive<P, S>(password: P, salt: S, iterations: u32, out: &mut [u8])
+where
+    P: AsRef<[u8]>,
+    S: AsRef<[u8]>,
+{
+    assert!(iterations > 0, "iteration count must not be 0");
+
+    // The password is only processed once, each round clones the keyed HMAC
+    let prf = Hmac::new(password);
+    let salt = salt.as_ref();
+
+    for (i, block) in out.chunks_mut(20).enumerate() {
+        let mut h = prf.clone();
+        h.update(salt);
+        h.update((i as u32 + 1).to_be_bytes());
+
+        let mut u = hash_to_bytes(&h.finish());
+        let mut t = u;
+
+        for _ in 1..iterations {
+            let mut h = prf.clone();
+            h.update(u);
+            u = hash_to_bytes(&h.finish());
+
+            for (t, u) in t.iter_mut().zip(&u) {
+                *t ^= u;
+            }
+        }
+
+        block.copy_from_slice(&t[..block.len()]);
+    }
+}
+
+/// Derives the 256 bit pre-shared key of a WPA2-Personal network from its SSID and passphrase,
+/// with 4096 rounds of PBKDF2 salted by the SSID (IEEE 802.11i annex H.4).
+///
+/// Panics if `ssid` is longer than 32 bytes, or `passphrase` is not 8 to 63 printable ASCII
+/// characters.
+pub fn wpa2_psk<S: AsRef<[u8]>>(ssid: S, passphrase: &str) -> [u8; 32] {
+    let ssid = ssid.as_ref();
+    assert!(ssid.len() <= 32, "SSID is longer than 32 bytes");
+    let printable = passphrase.bytes().all(|b| (32..=126).contains(&b));
+    assert!(
+        (8..=63).contains(&passphrase.len()) && printable,
+        "passphrase must be 8 to 63 printable ASCII characters"
+    );
+
+    let mut psk = [0; 32];
+    derive(passphrase, ssid, 4096, &mut psk);
+    psk
+}
@@ -0,0 +1,222 @@
+//! Hashing of fixed-size pieces, as used for torrent creation and chunked upload validation.
+//!
+//! With the `rayon` feature enabled, pieces are hashed in parallel.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::pieces;
+//! # fn f() -> std::io::Result<()> {
+//! // Hash every 256 KiB piece of a file
+//! let hashes = pieces::digest_path("foo.txt", 256 * 1024)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Hash, Sha1, Sha1Error};
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::{fs::File, path::Path};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Hashes everything from a reader in pieces of `piece_len` bytes and returns the hash of each
+/// piece, in order. The last piece is shorter if the total length is not a multiple of
+/// `piece_len`. Empty input produces no pieces.
+///
+/// Panics if `piece_len` is 0.
+pub fn digest_reader<R: Read>(mut reader: R, piece_len: usize) -> Result<Vec<Hash>, Sha1Error> {
+    assert!(piece_len > 0, "piece length must not be 0");
+
+    // Read as many pieces at once as there are threads to hash them with
+    #[cfg(feature = "rayon")]
+    let batch = rayon::current_num_threads().max(1);
+    #[cfg(not(feature = "rayon"))]
+    let batch = 1;
+
+    let mut buf = vec![0; piece_len * batch];
+    let mut hashes = Vec::new();
+
+    loop {
+        let n = read_full(&mut reader, &mut buf)?;
+
+        #[cfg(feature = "rayon")]
+        hashes.par_extend(buf[..n].par_chunks(piece_len).map(Sha1::digest));
+        #[cfg(not(feature = "rayon"))]
+        hashes.extend(buf[..n].chunks(piece_len).map(Sha1::digest));
+
+        // A short read means the reader is exhausted
+        if n < buf.len() {
+            return Ok(hashes);
+        }
+    }
+}
+
+/// Hashes a whole file by its path in pieces of `piece_len` bytes. See
+/// [`digest_reader`](fn.digest_reader.html).
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn digest_path<P: AsRef<Path>>(path: P, piece_len: usize) -> Result<Vec<Hash>, Sha1Error> {
+    digest_reader(File::open(path)?, piece_len)
+}
+
+/// Hashes a stream both as a whole and in parts of `part_size` bytes, for multipart uploads that
+/// checksum each part as well as the whole object.
+///
+/// Data can be added in pieces of any size; a part is finished whenever `part_size` bytes have
+/// been added to it. The last part is shorter if the total length is not a multiple of
+/// `part_size`, and empty input has no parts.
+///
+/// Example usage:
+/// ```
+/// # use sha1::{pieces::MultipartHasher, Sha1};
+/// let mut hasher = MultipartHasher::new(4);
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+/// let (parts, whole) = hasher.finish();
+/// assert_eq!(parts, [Sha1::digest(b"hell"), Sha1::digest(b"o wo"), Sha1::digest(b"rld")]);
+/// assert_eq!(whole, Sha1::digest(b"hello world"));
+/// ```
+#[derive(Clone)]
+pub struct MultipartHasher {
+    part_size: u64,
+    whole: Sha1,
+    part: Sha1,
+    parts: Vec<Hash>,
+}
+
+impl MultipartHasher {
+    /// Creates a new empty `MultipartHasher` that finishes a part every `part_size` bytes.
+    ///
+    /// Panics if `part_size` is 0.
+    pub fn new(part_size: u64) -> MultipartHasher {
+        assert!(part_size > 0, "part size must not be 0");
+
+        MultipartHasher {
+            part_size,
+            whole: Sha1::new(),
+            part: Sha1::new(),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Adds data to the whole hash and to the current part, finishing parts as they fill up.
+    pub fn update<D: AsRef<[u8]>>(&mut self, data: D) {
+        let mut data = data.as_ref();
+        self.whole.update(data);
+
+        while !data.is_empty() {
+            let left = self.part_size - self.part.bytes_processed();
+            let n = data.len().min(left.try_into().unwrap_or(usize::MAX));
+            self.part.update(&data[..n]);
+            data = &data[n..];
+
+            if self.part.bytes_processed() == self.part_size {
+                self.parts.push(self.part.finish_reset());
+            }
+        }
+    }
+
+    /// Returns the hashes of the parts finished so far, in order.
+    pub fn parts(&self) -> &[Hash] {
+        &self.parts
+    }
+
+    /// Returns the number of bytes added so far.
+    pub fn bytes_processed(&self) -> u64 {
+        self.whole.bytes_processed()
+    }
+
+    /// Finishes the last part, if it has any data, and returns the hashes of all parts in order
+    /// along with the hash of the whole stream.
+    pub fn finish(mut self) -> (Vec<Hash>, Hash) {
+        if self.part.bytes_processed() > 0 {
+            self.parts.push(self.part.finish());
+        }
+
+        (self.parts, self.whole.finish())
+    }
+}
+
+impl Write for MultipartHasher {
+    /// Adds all data to the hash by calling `self.update(data)` and returns `Ok(data.len())`.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// First window where two streams differ, found by `compare_streams()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Offset of the start of the window.
+    pub offset: u64,
+    /// Hash of the window in the first stream.
+    pub left: Hash,
+    /// Hash of the window in the second stream.
+    pub right: Hash,
+}
+
+/// Reads two readers in lockstep, hashing windows of `window_len` bytes of each, and returns the
+/// first window whose hashes differ, or `None` if both readers have the same contents. If one
+/// reader ends first, the window where it ends differs.
+///
+/// Panics if `window_len` is 0.
+pub fn compare_streams<A, B>(
+    mut left: A,
+    mut right: B,
+    window_len: usize,
+) -> Result<Option<Divergence>, Sha1Error>
+where
+    A: Read,
+    B: Read,
+{
+    assert!(window_len > 0, "window length must not be 0");
+
+    let mut left_buf = vec![0; window_len];
+    let mut right_buf = vec![0; window_len];
+    let mut offset = 0;
+
+    loop {
+        let left_n = read_full(&mut left, &mut left_buf)?;
+        let right_n = read_full(&mut right, &mut right_buf)?;
+
+        let left_hash = Sha1::digest(&left_buf[..left_n]);
+        let right_hash = Sha1::digest(&right_buf[..right_n]);
+        if left_hash != right_hash {
+            return Ok(Some(Divergence {
+                offset,
+                left: left_hash,
+                right: right_hash,
+            }));
+        }
+
+        // Both windows are the same, so both readers are exhausted together
+        if left_n < window_len {
+            return Ok(None);
+        }
+        offset += window_len as u64;
+    }
+}
+
+// Reads until buf is full or the reader hits EOF, returning the number of bytes read.
+pub(crate) fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(filled)
+}
@@ -0,0 +1,36 @@
+//! DER `DigestInfo` encoding of SHA-1 hashes, as signed by RSASSA-PKCS1-v1_5 (RFC 8017).
+//!
+//! The encoding is a fixed 15 byte prefix, which names SHA-1 as the algorithm, followed by the
+//! 20 bytes of the hash.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::{pkcs1, Sha1};
+//! let encoded = pkcs1::digest_info(&Sha1::digest(b"message"));
+//! assert_eq!(encoded[..15], pkcs1::DIGEST_INFO_PREFIX);
+//! assert_eq!(pkcs1::parse_digest_info(&encoded), Some(Sha1::digest(b"message")));
+//! ```
+
+use crate::{hash_to_bytes, Digest, Hash};
+use core::convert::TryInto;
+
+/// DER encoding of a `DigestInfo` with the SHA-1 algorithm identifier, up to the hash. From the
+/// notes of RFC 8017 section 9.2.
+pub const DIGEST_INFO_PREFIX: [u8; 15] = [
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+
+/// Returns the 35 byte DER encoding of a `DigestInfo` holding `hash`.
+pub fn digest_info(hash: &Hash) -> [u8; 35] {
+    let mut out = [0; 35];
+    out[..15].copy_from_slice(&DIGEST_INFO_PREFIX);
+    out[15..].copy_from_slice(&hash_to_bytes(hash));
+    out
+}
+
+/// Returns the hash in an encoded `DigestInfo`, or `None` if `encoded` is not exactly the SHA-1
+/// prefix followed by a hash.
+pub fn parse_digest_info(encoded: &[u8]) -> Option<Hash> {
+    let hash = encoded.strip_prefix(&DIGEST_INFO_PREFIX[..])?;
+    Some(Digest::from_bytes(hash.try_into().ok()?).words())
+}
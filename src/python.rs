@@ -0,0 +1,96 @@
+// Python extension module, enabled by the `python` feature. Build it with maturin, which uses
+// the settings in pyproject.toml.
+//
+// The interface follows hashlib:
+//
+//     import sha1
+//     h = sha1.Sha1(b"Hello, ")
+//     h.update(b"world")
+//     h.hexdigest()
+//     sha1.hexdigest(b"Hello, world")
+
+use crate::{hash_to_bytes, hex, Sha1};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::io;
+use std::path::PathBuf;
+
+#[pyclass(name = "Sha1", module = "sha1", skip_from_py_object)]
+#[derive(Clone)]
+struct PySha1 {
+    inner: Sha1,
+}
+
+#[pymethods]
+impl PySha1 {
+    #[new]
+    #[pyo3(signature = (data = None))]
+    fn new(data: Option<&[u8]>) -> PySha1 {
+        let mut inner = Sha1::new();
+        if let Some(data) = data {
+            inner.update(data);
+        }
+
+        PySha1 { inner }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn digest<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &hash_to_bytes(&self.inner.peek_finish()))
+    }
+
+    fn hexdigest(&self) -> String {
+        hex::encode(&self.inner.peek_finish())
+    }
+
+    fn copy(&self) -> PySha1 {
+        self.clone()
+    }
+
+    #[getter]
+    fn name(&self) -> &'static str {
+        "sha1"
+    }
+
+    #[getter]
+    fn digest_size(&self) -> usize {
+        20
+    }
+
+    #[getter]
+    fn block_size(&self) -> usize {
+        64
+    }
+}
+
+#[pyfunction]
+fn digest<'py>(py: Python<'py>, data: &[u8]) -> Bound<'py, PyBytes> {
+    PyBytes::new(py, &hash_to_bytes(&Sha1::digest(data)))
+}
+
+#[pyfunction]
+fn hexdigest(data: &[u8]) -> String {
+    hex::encode(&Sha1::digest(data))
+}
+
+#[pyfunction]
+fn hexdigest_path(py: Python<'_>, path: PathBuf) -> PyResult<String> {
+    // Hashing a large file can take a while, so let other Python threads run
+    let (hash, _) = py
+        .detach(|| Sha1::digest_path(path))
+        .map_err(io::Error::from)?;
+    Ok(hex::encode(&hash))
+}
+
+#[pymodule]
+#[pyo3(name = "sha1")]
+pub(crate) fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySha1>()?;
+    m.add_function(wrap_pyfunction!(digest, m)?)?;
+    m.add_function(wrap_pyfunction!(hexdigest, m)?)?;
+    m.add_function(wrap_pyfunction!(hexdigest_path, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::{Hash, Sha1, Sha1Error};
+use std::io::{self, Read};
+
+/// Reader adapter that hashes all bytes as they are read from the inner reader.
+///
+/// Example usage:
+/// ```
+/// # use sha1::HashingReader;
+/// # use std::fs::File;
+/// # fn f() -> std::io::Result<()> {
+/// // Copy a file somewhere else and hash it at the same time
+/// let mut reader = HashingReader::new(File::open("foo.txt")?);
+/// std::io::copy(&mut reader, &mut File::create("bar.txt")?)?;
+/// let hash = reader.digest();
+/// # Ok(())
+/// # }
+/// ```
+pub struct HashingReader<R> {
+    inner: R,
+    sha1: Sha1,
+    bytes: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    /// Creates a new `HashingReader` that reads from and hashes `inner`.
+    pub fn new(inner: R) -> HashingReader<R> {
+        HashingReader {
+            inner,
+            sha1: Sha1::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Returns the hash of all bytes read so far. Reading may continue afterwards.
+    pub fn digest(&self) -> Hash {
+        self.sha1.peek_finish()
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader. Bytes read directly from the inner
+    /// reader are not hashed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps the inner reader, discarding the hash.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    /// Reads from the inner reader and hashes the bytes that were read.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.sha1.update(&buf[..n]);
+        self.bytes += n as u64;
+        Ok(n)
+    }
+}
+
+/// Reader adapter that hashes all bytes as they are read, and fails at the end of the stream if
+/// their hash is not the expected one.
+///
+/// When the inner reader reaches its end and the hash doesn't match, `read()` returns an error of
+/// kind `io::ErrorKind::InvalidData` wrapping a `Sha1Error::Mismatch`, so functions like
+/// `io::copy()` fail instead of reporting success.
+///
+/// Example usage:
+/// ```
+/// # use sha1::VerifyingReader;
+/// # use std::fs::File;
+/// # fn f<R: std::io::Read>(download: R, expected: [u32; 5]) -> std::io::Result<()> {
+/// // Stream a download to disk, failing if it was corrupted
+/// let mut reader = VerifyingReader::new(download, expected);
+/// std::io::copy(&mut reader, &mut File::create("foo.iso")?)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct VerifyingReader<R> {
+    inner: HashingReader<R>,
+    expected: Hash,
+}
+
+impl<R: Read> VerifyingReader<R> {
+    /// Creates a new `VerifyingReader` that reads from `inner` and expects its data to have the
+    /// hash `expected`.
+    pub fn new(inner: R, expected: Hash) -> VerifyingReader<R> {
+        VerifyingReader {
+            inner: HashingReader::new(inner),
+            expected,
+        }
+    }
+
+    /// Compares the hash of all bytes read so far to the expected hash.
+    pub fn verify(&self) -> Result<(), Sha1Error> {
+        let actual = self.inner.digest();
+        if actual == self.expected {
+            Ok(())
+        } else {
+            Err(Sha1Error::Mismatch {
+                expected: self.expected,
+                actual,
+            })
+        }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.inner.bytes_read()
+    }
+
+    /// Returns a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        self.inner.get_ref()
+    }
+
+    /// Unwraps the inner reader, without verifying the data.
+    pub fn into_inner(self) -> R {
+        self.inner.into_inner()
+    }
+}
+
+impl<R: Read> Read for VerifyingReader<R> {
+    /// Reads from the inner reader and hashes the bytes that were read. At the end of the stream,
+    /// returns an error if the hash doesn't match.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.verify()?;
+        }
+
+        Ok(n)
+    }
+}
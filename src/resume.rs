@@ -0,0 +1,156 @@
+use crate::{Hash, Sha1, Sha1Error};
+use std::convert::TryInto;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+// Identifies checkpoint files, and their version
+const MAGIC: &[u8; 8] = b"SHA1CKP1";
+
+// Magic, file length, file modification time, and the exported hash state
+const CHECKPOINT_LEN: usize = 8 + 8 + 8 + Sha1::STATE_LEN;
+
+/// Hashes a large file while periodically saving a checkpoint, so that the hash can resume
+/// where it left off after a crash or reboot instead of starting over.
+///
+/// The checkpoint holds the file's length and modification time, and the state from
+/// `Sha1::export_state()`. It is ignored if it is damaged or the file has changed since it was
+/// written, and it is deleted once the hash is finished.
+///
+/// Example usage:
+/// ```
+/// # use sha1::ResumableFileHash;
+/// # fn f() -> std::io::Result<()> {
+/// // Saves foo.iso.sha1-checkpoint every 256 MiB
+/// let (hash, bytes) = ResumableFileHash::new("foo.iso").digest()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ResumableFileHash {
+    path: PathBuf,
+    checkpoint: PathBuf,
+    interval: u64,
+}
+
+impl ResumableFileHash {
+    /// Number of bytes hashed between checkpoints unless another interval is set with
+    /// `ResumableFileHash::interval()`.
+    pub const DEFAULT_INTERVAL: u64 = 256 * 1024 * 1024;
+
+    /// Creates a new `ResumableFileHash` for the file at `path`. The checkpoint is kept next to
+    /// it, with `.sha1-checkpoint` added to its name.
+    pub fn new<P: AsRef<Path>>(path: P) -> ResumableFileHash {
+        let path = path.as_ref().to_path_buf();
+        let mut checkpoint = path.clone().into_os_string();
+        checkpoint.push(".sha1-checkpoint");
+
+        ResumableFileHash {
+            path,
+            checkpoint: checkpoint.into(),
+            interval: ResumableFileHash::DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Sets where the checkpoint is kept.
+    pub fn checkpoint_path<P: AsRef<Path>>(mut self, path: P) -> ResumableFileHash {
+        self.checkpoint = path.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sets the number of bytes hashed between checkpoints.
+    ///
+    /// Panics if `bytes` is 0.
+    pub fn interval(mut self, bytes: u64) -> ResumableFileHash {
+        assert!(bytes > 0, "checkpoint interval must not be 0");
+        self.interval = bytes;
+        self
+    }
+
+    /// Hashes the whole file, resuming from the checkpoint if there is a valid one. Returns the
+    /// hash and the number of bytes in the file.
+    pub fn digest(&self) -> Result<(Hash, u64), Sha1Error> {
+        let mut file = File::open(&self.path)?;
+        let meta = file.metadata()?;
+
+        let mut s = self.load_checkpoint(&meta).unwrap_or_default();
+        let mut done = s.bytes_processed();
+        let mut saved = done;
+        file.seek(SeekFrom::Start(done))?;
+
+        let mut buf = vec![0; crate::FileHasher::DEFAULT_BUFFER_SIZE];
+        loop {
+            let n = match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+
+            s.update(&buf[..n]);
+            done += n as u64;
+
+            if done - saved >= self.interval {
+                self.save_checkpoint(&meta, &s)?;
+                saved = done;
+            }
+        }
+
+        // A missing checkpoint is fine, the file may have been hashed without one
+        match fs::remove_file(&self.checkpoint) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => (),
+        }
+
+        Ok((s.try_finish()?, done))
+    }
+
+    // Returns the saved hash, or None if there is no checkpoint, it is damaged, or it was made
+    // for another version of the file.
+    fn load_checkpoint(&self, meta: &Metadata) -> Option<Sha1> {
+        let data = fs::read(&self.checkpoint).ok()?;
+        if data.len() != CHECKPOINT_LEN || data[..8] != MAGIC[..] {
+            return None;
+        }
+        if data[16..24] != modified(meta) {
+            return None;
+        }
+
+        let s = Sha1::import_state(data[24..].try_into().unwrap()).ok()?;
+        let len = u64::from_be_bytes(data[8..16].try_into().unwrap());
+        if len != meta.len() || s.bytes_processed() > len || s.extra_bits != 0 {
+            return None;
+        }
+
+        Some(s)
+    }
+
+    // Writes the checkpoint to a temporary file first, so a crash while writing can't leave a
+    // damaged checkpoint behind in place of a good one.
+    pub(crate) fn save_checkpoint(&self, meta: &Metadata, s: &Sha1) -> io::Result<()> {
+        let mut data = Vec::with_capacity(CHECKPOINT_LEN);
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&meta.len().to_be_bytes());
+        data.extend_from_slice(&modified(meta));
+        data.extend_from_slice(&s.export_state());
+
+        let mut tmp = self.checkpoint.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut out = File::create(&tmp)?;
+        out.write_all(&data)?;
+        out.sync_all()?;
+        fs::rename(&tmp, &self.checkpoint)
+    }
+}
+
+// File modification time in nanoseconds since the Unix epoch, or 0 where it isn't available
+fn modified(meta: &Metadata) -> [u8; 8] {
+    let nanos = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as u64);
+
+    nanos.to_be_bytes()
+}
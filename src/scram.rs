@@ -0,0 +1,114 @@
+//! The key and proof computations of the SCRAM-SHA-1 authentication mechanism (RFC 5802), as
+//! used by MongoDB and XMPP.
+//!
+//! Exchanging the messages, and building the auth message from them, is left to the caller. The
+//! auth message is the client's first message without its GS2 header, the server's first
+//! message, and the client's final message without its proof, joined by commas.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::scram;
+//! # fn f(password: &[u8], salt: &[u8], iterations: u32, auth_message: &[u8], v: [u8; 20]) {
+//! // Client: prove knowledge of the password, then check that the server knew it too
+//! let salted = scram::salted_password(password, salt, iterations);
+//! let proof = scram::client_proof(&salted, auth_message);
+//! let server_key = scram::server_key(&salted);
+//! assert!(scram::verify_server_signature(&server_key, auth_message, &v));
+//! # }
+//! ```
+
+use crate::hash_to_bytes;
+use crate::hmac::Hmac;
+use crate::pbkdf2;
+use crate::Sha1;
+
+/// Returns `SaltedPassword`: PBKDF2 of the password with the salt and iteration count sent by
+/// the server. The password must already be normalized with SASLprep.
+///
+/// Panics if `iterations` is 0.
+pub fn salted_password<P, S>(password: P, salt: S, iterations: u32) -> [u8; 20]
+where
+    P: AsRef<[u8]>,
+    S: AsRef<[u8]>,
+{
+    let mut out = [0; 20];
+    pbkdf2::derive(password, salt, iterations, &mut out);
+    out
+}
+
+/// Returns `ClientKey`: the HMAC of "Client Key" keyed with the salted password.
+pub fn client_key(salted_password: &[u8; 20]) -> [u8; 20] {
+    hash_to_bytes(&Hmac::mac(salted_password, b"Client Key"))
+}
+
+/// Returns `StoredKey`: the hash of the client key. This is what a server stores instead of the
+/// password.
+pub fn stored_key(client_key: &[u8; 20]) -> [u8; 20] {
+    hash_to_bytes(&Sha1::digest(client_key))
+}
+
+/// Returns `ServerKey`: the HMAC of "Server Key" keyed with the salted password.
+pub fn server_key(salted_password: &[u8; 20]) -> [u8; 20] {
+    hash_to_bytes(&Hmac::mac(salted_password, b"Server Key"))
+}
+
+/// Returns `ClientProof`, which the client sends base64 encoded in the `p=` attribute of its
+/// final message.
+pub fn client_proof<A: AsRef<[u8]>>(salted_password: &[u8; 20], auth_message: A) -> [u8; 20] {
+    let client_key = client_key(salted_password);
+    let signature = client_signature(&stored_key(&client_key), auth_message);
+    xor(&client_key, &signature)
+}
+
+/// Returns `ServerSignature`, which the server sends base64 encoded in the `v=` attribute of its
+/// final message.
+pub fn server_signature<A: AsRef<[u8]>>(server_key: &[u8; 20], auth_message: A) -> [u8; 20] {
+    hash_to_bytes(&Hmac::mac(server_key, auth_message))
+}
+
+/// Client side: returns true if the server's signature is the expected one, which shows that the
+/// server knew the password.
+pub fn verify_server_signature<A>(server_key: &[u8; 20], auth_message: A, signature: &[u8]) -> bool
+where
+    A: AsRef<[u8]>,
+{
+    constant_time_eq(&server_signature(server_key, auth_message), signature)
+}
+
+/// Server side: returns true if the client's proof shows that it knew the password with this
+/// stored key.
+pub fn verify_client_proof<A>(stored_key: &[u8; 20], auth_message: A, proof: &[u8]) -> bool
+where
+    A: AsRef<[u8]>,
+{
+    if proof.len() != 20 {
+        return false;
+    }
+
+    // The proof is the client key XORed with the client signature, so XORing again recovers it
+    let mut client_key = client_signature(stored_key, auth_message);
+    for (k, p) in client_key.iter_mut().zip(proof) {
+        *k ^= p;
+    }
+
+    constant_time_eq(&self::stored_key(&client_key), stored_key)
+}
+
+// ClientSignature: the HMAC of the auth message keyed with the stored key.
+fn client_signature<A: AsRef<[u8]>>(stored_key: &[u8; 20], auth_message: A) -> [u8; 20] {
+    hash_to_bytes(&Hmac::mac(stored_key, auth_message))
+}
+
+fn xor(a: &[u8; 20], b: &[u8; 20]) -> [u8; 20] {
+    let mut out = *a;
+    for (o, b) in out.iter_mut().zip(b) {
+        *o ^= b;
+    }
+    out
+}
+
+// Compares without stopping at the first difference, so that the time taken doesn't reveal how
+// much of a guessed signature was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
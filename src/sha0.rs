@@ -0,0 +1,174 @@
+//! SHA-0, the original 1993 version of SHA-1 from FIPS 180.
+//!
+//! SHA-0 differs from SHA-1 only by leaving out the 1-bit rotate in the message schedule. It was
+//! withdrawn because of that weakness and is badly broken; it is only useful for reproducing
+//! hashes made by old systems.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::sha0::Sha0;
+//! let mut s = Sha0::new();
+//! s.update(b"First part of hashed data");
+//! s.update(b"Second part of hashed data");
+//! let hash = s.finish();
+//!
+//! let hash = Sha0::digest(b"Hello, world");
+//! ```
+
+use crate::compress::compress_portable;
+use crate::{Hash, INITIAL_STATE};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// SHA-0 Hash context. Represents one single hash, and works like `Sha1`.
+// SHA-0 has its own state rather than being a variant of Sha1, so that SHA-1 hashing doesn't
+// have to check which of the two it is computing for every chunk.
+#[derive(Clone)]
+pub struct Sha0 {
+    // 512 bit chunk
+    chunk: [u8; 64],
+
+    // Bytes currently used in the chunk, always less than 64 between calls
+    used: usize,
+
+    // Number of chunks that have been processed in the past
+    chunks_processed: u64,
+
+    // Hash value words
+    h: Hash,
+}
+
+impl Sha0 {
+    /// Creates a new empty `Sha0` struct. Usable in `const` and `static` contexts.
+    pub const fn new() -> Sha0 {
+        Sha0 {
+            chunk: [0; 64],
+            used: 0,
+            chunks_processed: 0,
+            h: INITIAL_STATE,
+        }
+    }
+
+    /// Re-initializes internal values to act like a brand new `Sha0` struct.
+    pub fn reset(&mut self) {
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(&mut self.chunk);
+
+        self.used = 0;
+        self.chunks_processed = 0;
+        self.h = INITIAL_STATE;
+    }
+
+    /// Utility function to simplify `Sha0` use when the whole message is available at once.
+    pub fn digest<D: AsRef<[u8]>>(data: D) -> Hash {
+        let mut s = Sha0::new();
+        s.update(data);
+        s.finish()
+    }
+
+    /// Adds `data` to the given hash.
+    pub fn update<D: AsRef<[u8]>>(&mut self, data: D) {
+        let mut data = data.as_ref();
+
+        while !data.is_empty() {
+            let n = data.len().min(64 - self.used);
+            self.chunk[self.used..self.used + n].copy_from_slice(&data[..n]);
+            self.used += n;
+            data = &data[n..];
+
+            if self.used == 64 {
+                self.process_chunk();
+            }
+        }
+    }
+
+    /// Finishes all work for a given hash and returns the final result.
+    pub fn finish(mut self) -> Hash {
+        self.finalize()
+    }
+
+    /// Finishes all work for a given hash, returns the final result, and resets the `Sha0`
+    /// struct so it can be used for the next hash.
+    pub fn finish_reset(&mut self) -> Hash {
+        let hash = self.finalize();
+        self.reset();
+        hash
+    }
+
+    /// Returns the hash of all data added so far without finishing the `Sha0` struct.
+    pub fn peek_finish(&self) -> Hash {
+        self.clone().finalize()
+    }
+
+    // Pads and processes the final chunk. Leaves self in a state that produces incorrect hashes
+    // until it is reset. Panics if the message length overflows.
+    fn finalize(&mut self) -> Hash {
+        let message_length = self
+            .chunks_processed
+            .checked_mul(512)
+            .and_then(|bits| bits.checked_add(8 * self.used as u64))
+            .expect("message is longer than SHA-0's limit of 2^64 - 1 bits");
+
+        // Add byte 10000000 and zeroes, spilling into one more chunk if the length doesn't fit
+        let used = self.used;
+        self.chunk[used] = 0x80;
+        self.chunk[used + 1..].fill(0);
+        if used >= 56 {
+            self.process_chunk();
+            self.chunk = [0; 64];
+        }
+
+        self.chunk[56..].copy_from_slice(&message_length.to_be_bytes());
+        self.process_chunk();
+        self.h
+    }
+
+    fn process_chunk(&mut self) {
+        self.chunks_processed += 1;
+        self.used = 0;
+        self.h = compress_portable::<true>(self.h, &self.chunk);
+    }
+}
+
+impl Default for Sha0 {
+    /// Equivalent to `Sha0::new()`.
+    fn default() -> Sha0 {
+        Sha0::new()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Sha0 {
+    /// Zeroes all internal state. The `Sha0` struct must be reset before it is used again.
+    fn zeroize(&mut self) {
+        self.chunk.zeroize();
+        self.used.zeroize();
+        self.chunks_processed.zeroize();
+        self.h.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Sha0 {
+    /// Zeroes all internal state, so hashed data does not linger in memory.
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Sha0 {}
+
+#[cfg(feature = "std")]
+impl Write for Sha0 {
+    /// Writes all data to hasher by calling `self.update(data)` and returns `Ok(data.len())`.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
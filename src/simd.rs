@@ -0,0 +1,26 @@
+// Message schedule using std::simd, for targets without a schedule written with their own SIMD
+// instructions. Only compiled with the portable-simd feature, which needs a nightly compiler.
+
+use core::simd::u32x4;
+
+// Fills the 80 word message schedule for a chunk.
+pub(crate) fn schedule(chunk: &[u8; 64], w: &mut [u32; 80]) {
+    for i in 0..16 {
+        let j = i * 4;
+        w[i] = u32::from_be_bytes([chunk[j], chunk[j + 1], chunk[j + 2], chunk[j + 3]]);
+    }
+
+    // Each word here depends on the word 3 before it, so they can't be computed 4 at a time
+    for i in 16..32 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    // With this form of the recurrence, every word depends only on words at least 6 before it,
+    // so 4 words at a time can be computed at once
+    for i in (32..80).step_by(4) {
+        let load = |j: usize| u32x4::from_slice(&w[j..j + 4]);
+        let x = load(i - 6) ^ load(i - 16) ^ load(i - 28) ^ load(i - 32);
+        let x = (x << u32x4::splat(2)) | (x >> u32x4::splat(30));
+        x.copy_to_slice(&mut w[i..i + 4]);
+    }
+}
@@ -0,0 +1,51 @@
+//! SNMPv3 User-based Security Model key helpers for SHA-1 (RFC 3414 section A.2.2).
+//!
+//! Example usage:
+//! ```
+//! # use sha1::snmp;
+//! // Derive the authentication key a user shares with one SNMP engine
+//! let engine_id = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+//! let key = snmp::localize_key(&snmp::password_to_key(b"maplesyrup"), &engine_id);
+//! ```
+
+use crate::{hash_to_bytes, Sha1};
+
+// Number of bytes of the repeated password that are hashed
+const STRETCH_LEN: usize = 1024 * 1024;
+
+/// Turns a password into a 20 byte master key by hashing 1 MiB of the password repeated over
+/// and over.
+///
+/// Panics if `password` is empty.
+pub fn password_to_key<P: AsRef<[u8]>>(password: P) -> [u8; 20] {
+    let password = password.as_ref();
+    assert!(!password.is_empty(), "password must not be empty");
+
+    // Hash the repeated password 64 bytes at a time, like the RFC's reference code
+    let mut s = Sha1::new();
+    let mut block = [0; 64];
+    let mut index = 0;
+
+    for _ in 0..STRETCH_LEN / 64 {
+        for b in block.iter_mut() {
+            *b = password[index % password.len()];
+            index += 1;
+        }
+        s.update(block);
+    }
+
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(&mut block);
+
+    hash_to_bytes(&s.finish())
+}
+
+/// Localizes a master key from `password_to_key()` to one SNMP engine, by hashing the key, the
+/// engine ID, and the key again.
+pub fn localize_key(key: &[u8; 20], engine_id: &[u8]) -> [u8; 20] {
+    let mut s = Sha1::new();
+    s.update(key);
+    s.update(engine_id);
+    s.update(key);
+    hash_to_bytes(&s.finish())
+}
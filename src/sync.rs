@@ -0,0 +1,101 @@
+use crate::{Hash, Sha1};
+use std::io::{self, Write};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// `Sha1` behind a mutex, so that several threads can add data to one hash through a shared
+/// reference.
+///
+/// Each call to `update()` or `write()` is added as a whole, but calls from different threads
+/// are added in whatever order they take the lock. Threads that write segments of one stream
+/// need to take turns in the stream's order themselves.
+///
+/// Example usage:
+/// ```
+/// # use sha1::SyncSha1;
+/// # use std::io::Write;
+/// let hasher = SyncSha1::new();
+/// std::thread::scope(|s| {
+///     s.spawn(|| (&hasher).write_all(b"written from another thread"));
+/// });
+/// let hash = hasher.finish();
+/// ```
+#[derive(Default)]
+pub struct SyncSha1 {
+    inner: Mutex<Sha1>,
+}
+
+impl SyncSha1 {
+    /// Creates a new empty `SyncSha1` struct.
+    pub fn new() -> SyncSha1 {
+        SyncSha1 {
+            inner: Mutex::new(Sha1::new()),
+        }
+    }
+
+    /// Adds data to the hash, blocking until no other thread is adding data.
+    pub fn update<D: AsRef<[u8]>>(&self, data: D) {
+        self.lock().update(data);
+    }
+
+    /// Returns the hash of all data added so far. Data can still be added afterwards.
+    pub fn peek_finish(&self) -> Hash {
+        self.lock().peek_finish()
+    }
+
+    /// Returns the number of bytes added so far.
+    pub fn bytes_processed(&self) -> u64 {
+        self.lock().bytes_processed()
+    }
+
+    /// Finishes all work for the hash and returns the final result.
+    pub fn finish(self) -> Hash {
+        self.into_inner().finish()
+    }
+
+    /// Unwraps the inner `Sha1` struct.
+    pub fn into_inner(self) -> Sha1 {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // A thread that panicked while holding the lock can't have left the hash half-updated, since
+    // Sha1::update() only panics before it changes anything, so poisoning is ignored.
+    fn lock(&self) -> MutexGuard<'_, Sha1> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl From<Sha1> for SyncSha1 {
+    /// Wraps an in-progress hash, so that more data can be added to it from several threads.
+    fn from(sha1: Sha1) -> SyncSha1 {
+        SyncSha1 {
+            inner: Mutex::new(sha1),
+        }
+    }
+}
+
+impl Write for &SyncSha1 {
+    /// Adds all data to the hash by calling `self.update(data)` and returns `Ok(data.len())`.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Write for SyncSha1 {
+    /// Same as writing to `&SyncSha1`.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        (&*self).write(data)
+    }
+
+    /// Does nothing and returns `Ok(())`.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,13 +1,14 @@
 use super::*;
+use crate::compress::*;
 use std::convert::TryInto;
-use std::iter::repeat;
+use std::io::{self, Read, Write};
 
 #[test]
 fn reset_test() {
     // Test that reset Sha1 structs act the same as new Sha1 structs
     let mut s = Sha1::new();
     s.update(b"hello, world :^)");
-    s.finish();
+    s.finalize();
     s.reset();
     assert!(s.h0 == 0x67452301);
     assert!(s.h1 == 0xEFCDAB89);
@@ -21,7 +22,7 @@ fn reset_test() {
 fn update_test() {
     // Test that update does not leave a chunk full without processing it
     let mut s = Sha1::new();
-    let data: Vec<u8> = repeat(b'a').take(64).collect();
+    let data = vec![b'a'; 64];
     s.update(&data);
     assert!(s.used == 0);
     assert!(s.chunks_processed == 1);
@@ -33,14 +34,2048 @@ fn general_test() {
     let mut data: Vec<u8> = Vec::with_capacity(1000);
 
     for n in 0..300 {
+        assert!(Sha1::digest(&data) == known_good_hash(&data), "{} x a", n);
+        data.push(b'a');
+    }
+}
+
+#[test]
+fn digest_reader_test() {
+    // Test that hashing a reader matches hashing the same bytes directly
+    let data = vec![b'a'; 1000];
+    let (hash, bytes) = Sha1::digest_reader(&data[..]).unwrap();
+    assert!(bytes == 1000);
+    assert!(hash == known_good_hash(&data));
+}
+
+#[test]
+fn digest_path_test() {
+    // Test that hashing a file by path matches hashing its contents, for both small files and
+    // files large enough to be memory-mapped with the mmap feature
+    for &len in &[0, 100, 3 << 20] {
+        let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let path = std::env::temp_dir().join(format!("sha1-digest-path-test-{}", len));
+        std::fs::write(&path, &data).unwrap();
+
+        let result = Sha1::digest_path(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let (hash, bytes) = result.unwrap();
+        assert!(bytes == len as u64);
+        assert!(hash == known_good_hash(&data));
+    }
+}
+
+#[test]
+fn pieces_test() {
+    // Test that each piece hashes like the matching slice of the input, including a short
+    // final piece
+    let data: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+
+    for &piece_len in &[1, 64, 1000, 4096, 10_000, 20_000] {
+        let hashes = pieces::digest_reader(&data[..], piece_len).unwrap();
+        let expected: Vec<Hash> = data.chunks(piece_len).map(known_good_hash).collect();
+        assert!(hashes == expected, "piece length {}", piece_len);
+    }
+
+    assert!(pieces::digest_reader(&b""[..], 64).unwrap().is_empty());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn tokio_async_write_test() {
+    // Test that tokio::io::copy into a Sha1 hashes everything from the reader
+    let data = vec![b'a'; 1000];
+    let mut s = Sha1::new();
+    let bytes = tokio::io::copy(&mut &data[..], &mut s).await.unwrap();
+    assert!(bytes == 1000);
+    assert!(s.finish() == known_good_hash(&data));
+}
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn digest_async_reader_test() {
+    // Test that hashing an async reader matches hashing the same bytes directly
+    let data = vec![b'a'; 20_000];
+    let (hash, bytes) = Sha1::digest_async_reader(&data[..]).await.unwrap();
+    assert!(bytes == 20_000);
+    assert!(hash == known_good_hash(&data));
+}
+
+#[cfg(feature = "futures")]
+#[tokio::test]
+async fn digest_stream_test() {
+    // Test that hashing a stream of buffers matches hashing them concatenated, and that the
+    // stream's first error is returned
+    let data: Vec<u8> = (0..20_000u32).map(|i| i as u8).collect();
+    let chunks = data.chunks(777).map(|c| Ok::<_, io::Error>(c.to_vec()));
+    let (hash, bytes) = Sha1::digest_stream(futures_util::stream::iter(chunks))
+        .await
+        .unwrap();
+    assert!(bytes == 20_000);
+    assert!(hash == known_good_hash(&data));
+
+    let items = vec![Ok(vec![1, 2, 3]), Err("body ended early"), Ok(vec![4])];
+    let result = Sha1::digest_stream(futures_util::stream::iter(items)).await;
+    assert!(result == Err("body ended early"));
+}
+
+#[test]
+fn hashing_reader_test() {
+    // Test that everything passed through a HashingReader is hashed
+    let data: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+    let mut reader = HashingReader::new(&data[..]);
+    let mut out = Vec::new();
+
+    reader.read_to_end(&mut out).unwrap();
+    assert!(out == data);
+    assert!(reader.bytes_read() == 5000);
+    assert!(reader.digest() == known_good_hash(&data));
+}
+
+#[test]
+fn hashing_writer_test() {
+    // Test that everything passed through a HashingWriter is hashed
+    let data: Vec<u8> = (0..5000).map(|i| i as u8).collect();
+    let mut writer = HashingWriter::new(Vec::new());
+
+    writer.write_all(&data).unwrap();
+    assert!(writer.bytes_written() == 5000);
+
+    let (out, hash) = writer.finish();
+    assert!(out == data);
+    assert!(hash == known_good_hash(&data));
+}
+
+#[test]
+fn state_test() {
+    // Test that a hash exported and imported part way through produces the same result
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+
+    for &split in &[0, 1, 63, 64, 65, 500, 1000] {
+        let mut s = Sha1::new();
+        s.update(&data[..split]);
+
+        let mut s = Sha1::import_state(&s.export_state()).unwrap();
+        s.update(&data[split..]);
+        assert!(s.finish() == known_good_hash(&data), "split at {}", split);
+    }
+
+    let mut bad_state = Sha1::new().export_state();
+    bad_state[29] = 64;
+    assert!(Sha1::import_state(&bad_state).err() == Some(Sha1Error::InvalidState));
+
+    // Test that states from unknown versions of the layout are rejected
+    for &version in &[0, 2, 255] {
+        let mut state = Sha1::new().export_state();
+        state[0] = version;
+        assert!(Sha1::import_state(&state).err() == Some(Sha1Error::InvalidState));
+    }
+}
+
+#[test]
+fn length_extension_test() {
+    // Test that extending a hash matches hashing the original message, its padding, and the
+    // extension together
+    let suffix = b";admin=true";
+
+    for len in 0..200 {
+        let message = vec![b'a'; len];
+        let mut s = Sha1::from_digest(Sha1::digest(&message), len as u64).unwrap();
+        s.update(suffix);
+
+        let mut forged = message.clone();
+        forged.extend(Sha1::padding(len as u64));
+        assert!(forged.len() % 64 == 0);
+        forged.extend(suffix);
+
+        assert!(s.finish() == known_good_hash(&forged), "{} x a", len);
+    }
+
+    // Test that messages whose padded length is 2^64 bits or more are rejected instead of
+    // overflowing. The largest padded length that fits is 2^61 - 64 bytes, with 9 bytes of padding
+    let hash = known_good_hash(b"");
+    let max = (1 << 61) - 73;
+    let s = Sha1::from_digest(hash, max).unwrap();
+    assert!(s.try_finish().is_ok());
+    for &len in &[
+        max + 1,
+        (1 << 61) - 1,
+        u64::MAX / 8 + 1,
+        u64::MAX - 8,
+        u64::MAX,
+    ] {
+        assert!(Sha1::from_digest(hash, len).unwrap_err() == Sha1Error::LengthOverflow);
+        assert!(len.wrapping_add(Sha1::padding(len).len() as u64) % 64 == 0);
+    }
+}
+
+#[test]
+fn peek_finish_test() {
+    // Test that peeking at the hash does not affect hashing of later data
+    let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    let mut s = Sha1::new();
+
+    for n in 0..data.len() {
         assert!(
-            Sha1::digest(&data) == known_good_hash(&data),
-            format!("{} x a", n)
+            s.peek_finish() == known_good_hash(&data[..n]),
+            "{} bytes",
+            n
         );
+        s.update(&data[n..n + 1]);
+    }
+
+    assert!(s.finish() == known_good_hash(&data));
+}
+
+#[test]
+fn finish_reset_test() {
+    // Test that one Sha1 struct can hash many messages in a row
+    let mut s = Sha1::new();
+    let mut data = Vec::new();
+
+    for n in 0..100 {
+        s.update(&data);
+        assert!(s.finish_reset() == known_good_hash(&data), "{} x a", n);
         data.push(b'a');
     }
 }
 
+#[test]
+fn const_new_test() {
+    // Test that const and default Sha1 structs act like new Sha1 structs
+    const S: Sha1 = Sha1::new();
+    assert!(S.finish() == known_good_hash(b""));
+    assert!(Sha1::default().finish() == known_good_hash(b""));
+}
+
+#[test]
+fn fmt_write_test() {
+    // Test that formatted writes hash the same bytes as the formatted string
+    let mut s = Sha1::new();
+    fmt::Write::write_fmt(&mut s, format_args!("{}:{}", 1234, "version")).unwrap();
+    assert!(s.finish() == known_good_hash(b"1234:version"));
+}
+
+#[test]
+fn extend_test() {
+    // Test that bytes from iterators hash the same as the collected bytes
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    let evens: Vec<u8> = data.iter().copied().filter(|b| b % 2 == 0).collect();
+
+    let mut s = Sha1::new();
+    s.update(&data[..10]);
+    s.extend(&data[10..]);
+    assert!(s.finish() == known_good_hash(&data));
+
+    let s: Sha1 = data.iter().copied().filter(|b| b % 2 == 0).collect();
+    assert!(s.finish() == known_good_hash(&evens));
+}
+
+#[test]
+fn hex_test() {
+    // Test hex round trips, and that either case is accepted
+    let hash = known_good_hash(b"Hello, world");
+    let s = hex::encode(&hash);
+    assert!(s == "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8");
+    assert!(hex::decode(&s) == Ok(hash));
+    assert!(hex::decode(&s.to_uppercase()) == Ok(hash));
+    assert!(hex::decode(&s[1..]) == Err(Sha1Error::InvalidHex));
+    assert!(hex::decode(&s.replace('e', "g")) == Err(Sha1Error::InvalidHex));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_test() {
+    // Test that hashes and states survive a round trip through JSON
+    let hash = known_good_hash(b"Hello, world");
+
+    let mut json = Vec::new();
+    hash_serde::hex::serialize(&hash, &mut serde_json::Serializer::new(&mut json)).unwrap();
+    assert!(json == b"\"e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8\"");
+    let mut de = serde_json::Deserializer::from_slice(&json);
+    assert!(hash_serde::hex::deserialize(&mut de).unwrap() == hash);
+
+    let mut json = Vec::new();
+    hash_serde::bytes::serialize(&hash, &mut serde_json::Serializer::new(&mut json)).unwrap();
+    let mut de = serde_json::Deserializer::from_slice(&json);
+    assert!(hash_serde::bytes::deserialize(&mut de).unwrap() == hash);
+
+    let mut s = Sha1::new();
+    s.update(b"Hello, ");
+    let json = serde_json::to_string(&s).unwrap();
+    let mut s: Sha1 = serde_json::from_str(&json).unwrap();
+    s.update(b"world");
+    assert!(s.finish() == hash);
+
+    // Test that hex strings don't have to be borrowed from the input, as with readers or
+    // escaped strings
+    let escaped = b"\"\\u0065\\u00302aa1b106d5c7c6a98def2b13005d5b84fd8dc8\"";
+    let mut de = serde_json::Deserializer::from_reader(&escaped[..]);
+    assert!(hash_serde::hex::deserialize(&mut de).unwrap() == hash);
+
+    // Test that digests are hex in JSON and bytes in a binary format
+    let digest = Digest::from(hash);
+    let json = serde_json::to_vec(&digest).unwrap();
+    assert!(json == b"\"e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8\"");
+    let decoded: Digest = serde_json::from_reader(&json[..]).unwrap();
+    assert!(decoded == digest);
+    let value = serde_json::to_value(digest).unwrap();
+    assert!(serde_json::from_value::<Digest>(value).unwrap() == digest);
+    assert!(serde_json::from_slice::<Digest>(b"\"e02a\"").is_err());
+
+    let cbor = serde_cbor::to_vec(&digest).unwrap();
+    assert!(cbor.len() == 21 && cbor[1..] == digest.bytes());
+    assert!(serde_cbor::from_slice::<Digest>(&cbor).unwrap() == digest);
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize_test() {
+    // Test that buffered data is wiped by reset and zeroize
+    let mut s = Sha1::new();
+    s.update(b"secret");
+    s.reset();
+    assert!(s.chunk == [0; 64]);
+
+    s.update(b"secret");
+    zeroize::Zeroize::zeroize(&mut s);
+    assert!(s.chunk == [0; 64]);
+    assert!(s.used == 0 && s.chunks_processed == 0);
+    assert!([s.h0, s.h1, s.h2, s.h3, s.h4] == [0; 5]);
+}
+
+#[test]
+fn manifest_test() {
+    use manifest::{Entry, Format, Manifest, Status};
+
+    // Test parsing and formatting of each line format
+    let hash = known_good_hash(b"Hello, world");
+    let lines = [
+        (
+            "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8  a b.txt",
+            Format::Gnu,
+        ),
+        (
+            "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8 *a b.txt",
+            Format::GnuBinary,
+        ),
+        (
+            "SHA1 (a b.txt) = e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8",
+            Format::Bsd,
+        ),
+    ];
+
+    for &(line, format) in &lines {
+        let entry = Entry::parse(line).unwrap();
+        assert!(entry.hash == hash && entry.path == "a b.txt" && entry.format == format);
+        assert!(entry.to_string() == line);
+    }
+
+    assert!(Entry::parse("e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8 a.txt").is_none());
+    assert!(Entry::parse("SHA1 (a.txt) = e02aa1b1").is_none());
+
+    // Test reading a manifest and verifying the files in it
+    let dir = std::env::temp_dir().join("sha1-manifest-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("good"), b"Hello, world").unwrap();
+    std::fs::write(dir.join("bad"), b"Goodbye, world").unwrap();
+
+    let text = "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8  good\n\
+                \n\
+                SHA1 (bad) = e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8\n\
+                not a checksum\n\
+                e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8  missing\n";
+    let manifest = Manifest::read(text.as_bytes()).unwrap();
+    assert!(manifest.entries.len() == 3);
+    assert!(manifest.bad_lines == [4]);
+
+    let results = manifest.verify(&dir);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(results[0].1 == Status::Ok);
+    assert!(results[1].1 == Status::Mismatch(known_good_hash(b"Goodbye, world")));
+    assert!(results[2].1 == Status::Missing);
+
+    let mut out = Vec::new();
+    manifest.write(&mut out).unwrap();
+    assert!(Manifest::read(&out[..]).unwrap().entries == manifest.entries);
+}
+
+#[test]
+fn hash_dir_test() {
+    use dir::{hash_dir, DirOptions};
+
+    // Test that every file in a tree is hashed, and that filters apply
+    let root = std::env::temp_dir().join("sha1-hash-dir-test");
+    std::fs::create_dir_all(root.join("sub/skip")).unwrap();
+    std::fs::write(root.join("a.txt"), b"a").unwrap();
+    std::fs::write(root.join("b.bin"), b"b").unwrap();
+    std::fs::write(root.join("sub/c.txt"), b"c").unwrap();
+    std::fs::write(root.join("sub/skip/d.txt"), b"d").unwrap();
+
+    let all = hash_dir(&root, &DirOptions::new()).unwrap();
+    let filtered = DirOptions::new()
+        .include(|p| p.extension() == Some("txt".as_ref()))
+        .exclude(|p| p.ends_with("skip"));
+    let filtered = hash_dir(&root, &filtered).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let expected = |files: &[(&str, &[u8])]| -> Vec<_> {
+        files
+            .iter()
+            .map(|&(name, data)| (root.join(name), known_good_hash(data)))
+            .collect()
+    };
+
+    let a: (&str, &[u8]) = ("a.txt", b"a");
+    let c: (&str, &[u8]) = ("sub/c.txt", b"c");
+    assert!(all == expected(&[a, ("b.bin", b"b"), c, ("sub/skip/d.txt", b"d")]));
+    assert!(filtered == expected(&[a, c]));
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn update_buf_test() {
+    use bytes::Buf;
+
+    // Test that a chain of buffers hashes like the concatenated data
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    let buf = Buf::chain(Buf::chain(&data[..10], &data[10..500]), &data[500..]);
+
+    let mut s = Sha1::new();
+    s.update_buf(buf);
+    assert!(s.finish() == known_good_hash(&data));
+}
+
+#[test]
+fn torrent_test() {
+    // Test that piece hashes are concatenated in order
+    let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+    let pieces = torrent::piece_hashes(&data[..], 300).unwrap();
+    assert!(pieces.len() == 4 * 20);
+    assert!(pieces[60..] == hash_to_bytes(&known_good_hash(&data[900..])));
+
+    // Test finding the info dictionary among other keys
+    let info = b"d6:lengthi1000e4:name3:foo12:piece lengthi300e6:pieces0:e";
+    let mut file = b"d8:announce3:url4:listl1:ai2ee".to_vec();
+    file.extend_from_slice(b"4:info");
+    file.extend_from_slice(info);
+    file.extend_from_slice(b"e");
+
+    assert!(torrent::find_info(&file) == Some(&info[..]));
+    assert!(torrent::info_hash(info) == hash_to_bytes(&known_good_hash(info)));
+    assert!(torrent::find_info(b"d8:announce3:urle").is_none());
+    assert!(torrent::find_info(b"d4:info99:short").is_none());
+
+    // Test that deeply nested values are skipped without overflowing the stack
+    let depth = 1_000_000;
+    let info = [vec![b'l'; depth], vec![b'e'; depth]].concat();
+    let file = [&b"d4:info"[..], &info, b"e"].concat();
+    assert!(torrent::find_info(&file) == Some(&info[..]));
+    assert!(torrent::find_info(&file[..file.len() - 2]).is_none());
+    assert!(torrent::find_info(&[&b"d4:info"[..], &vec![b'd'; depth]].concat()).is_none());
+}
+
+#[test]
+fn hmac_test() {
+    // RFC 2202 test cases, including keys longer than one block
+    let cases: [(&[u8], &[u8], &str); 4] = [
+        (
+            &[0x0b; 20],
+            b"Hi There",
+            "b617318655057264e28bc0b6fb378c8ef146be00",
+        ),
+        (
+            b"Jefe",
+            b"what do ya want for nothing?",
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+        ),
+        (
+            &[0xaa; 20],
+            &[0xdd; 50],
+            "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+        ),
+        (
+            &[0xaa; 80],
+            b"Test Using Larger Than Block-Size Key - Hash Key First",
+            "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+        ),
+    ];
+
+    for &(key, data, expected) in &cases {
+        assert!(hex::encode(&hmac::Hmac::mac(key, data)) == expected);
+    }
+}
+
+#[test]
+fn base64_test() {
+    // RFC 4648 test vectors
+    let cases = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    for &(data, expected) in &cases {
+        assert!(base64::encode(data.as_bytes()) == expected);
+    }
+}
+
+#[test]
+fn oauth1_test() {
+    // Example request from Twitter's documentation on creating signatures
+    let params = [
+        (
+            "status",
+            "Hello Ladies + Gentlemen, a signed OAuth request!",
+        ),
+        ("include_entities", "true"),
+        ("oauth_consumer_key", "xvz1evFS4wEEPTGEFPHBog"),
+        ("oauth_nonce", "kYjzVBB8Y0ZFabxSWbWovY3uYSQ2pTgmZeNu2VS4cg"),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", "1318622958"),
+        (
+            "oauth_token",
+            "370773112-GmHxMAgYyLbNEtIKZeRNFsMKPR9EyMZeS9weJAEb",
+        ),
+        ("oauth_version", "1.0"),
+    ];
+
+    let signature = oauth1::signature(
+        "post",
+        "https://api.twitter.com/1.1/statuses/update.json",
+        &params,
+        "kAcSOqF21Fu85e7zjz7ZN2U4ZRhfV3WpwPAoE3Z7kBw",
+        "LswwdoUaIvS8ltyTt5jkRh4J50vUPVVHtR2YPi5kE",
+    );
+
+    assert!(signature == "hCtSmYh+iHYCEqBWrE7C7hYmtUk=");
+    assert!(oauth1::percent_encode(&signature) == "hCtSmYh%2BiHYCEqBWrE7C7hYmtUk%3D");
+    assert!(oauth1::percent_encode("a b~ü") == "a%20b~%C3%BC");
+}
+
+#[test]
+fn digest_base64_test() {
+    // RFC 6455 WebSocket handshake example
+    let mut s = Sha1::new();
+    s.update(b"dGhlIHNhbXBsZSBub25jZQ==");
+    s.update(b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11");
+    let digest = Digest::from(s.finish());
+    assert!(digest.to_base64() == "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    assert!(digest.to_base64_url() == "s3pPLMBiTxaQ9kYGzzhZRbK-xOo");
+}
+
+#[test]
+fn base32_test() {
+    // RFC 4648 test vectors
+    let cases = [
+        ("", ""),
+        ("f", "MY======"),
+        ("fo", "MZXQ===="),
+        ("foo", "MZXW6==="),
+        ("foob", "MZXW6YQ="),
+        ("fooba", "MZXW6YTB"),
+        ("foobar", "MZXW6YTBOI======"),
+    ];
+
+    for &(data, expected) in &cases {
+        assert!(base32::encode(data.as_bytes(), true) == expected);
+        assert!(base32::encode(data.as_bytes(), false) == expected.trim_end_matches('='));
+    }
+
+    let digest = Digest::from(known_good_hash(b"Hello, world"));
+    assert!(digest.to_base32() == "4AVKDMIG2XD4NKMN54VRGAC5LOCP3DOI");
+}
+
+#[test]
+fn update_bits_test() {
+    // NIST SHAVS bit-oriented example
+    let mut s = Sha1::new();
+    s.update_bits([0x98], 5);
+    assert!(hex::encode(&s.finish()) == "29826b003b906e660eff4027ce98af3531ac75ba");
+
+    // Test against manually padded messages of every bit length around chunk boundaries
+    let data: Vec<u8> = (0..130).map(|i| (i * 37) as u8).collect();
+
+    for bit_len in 400..1040 {
+        let mut s = Sha1::new();
+        s.update_bits(&data, bit_len);
+        let state = Sha1::import_state(&s.export_state()).unwrap();
+
+        // Message bits, a 1 bit, zero bits, and the 64 bit length
+        let mut padded = data[..bit_len.div_ceil(8)].to_vec();
+        if bit_len % 8 == 0 {
+            padded.push(0x80);
+        } else {
+            let last = padded.last_mut().unwrap();
+            *last = (*last & !(0xff >> (bit_len % 8))) | (0x80 >> (bit_len % 8));
+        }
+        while padded.len() % 64 != 56 {
+            padded.push(0);
+        }
+        padded.extend(&(bit_len as u64).to_be_bytes());
+
+        let mut expected = Sha1::new();
+        for chunk in padded.chunks(64) {
+            expected.chunk.copy_from_slice(chunk);
+            expected.process_chunk();
+        }
+        let expected = [
+            expected.h0,
+            expected.h1,
+            expected.h2,
+            expected.h3,
+            expected.h4,
+        ];
+
+        assert!(s.finish() == expected, "{} bits", bit_len);
+        assert!(state.finish() == expected, "{} bits after import", bit_len);
+    }
+}
+
+#[test]
+#[should_panic]
+fn update_after_bits_test() {
+    // Test that adding whole bytes after a partial byte panics
+    let mut s = Sha1::new();
+    s.update_bits([0xff], 3);
+    s.update(b"a");
+}
+
+#[test]
+fn length_overflow_test() {
+    // Test the largest supported length, and one chunk more than that
+    let mut s = Sha1::new();
+    s.chunks_processed = (1 << 55) - 1;
+    s.update([0; 63]);
+    assert!(s.clone().try_finish().is_ok());
+
+    s.update([0]);
+    assert!(s.try_finish() == Err(Sha1Error::LengthOverflow));
+}
+
+#[test]
+#[should_panic]
+fn length_overflow_finish_test() {
+    let mut s = Sha1::new();
+    s.chunks_processed = 1 << 55;
+    s.finish();
+}
+
+#[test]
+fn introspection_test() {
+    // Test that byte counts track updates across chunk boundaries
+    let mut s = Sha1::new();
+    assert!(s.is_fresh());
+
+    s.update([0; 100]);
+    assert!(s.bytes_processed() == 100 && s.buffered_len() == 36 && !s.is_fresh());
+
+    s.update([0; 28]);
+    assert!(s.bytes_processed() == 128 && s.buffered_len() == 0);
+
+    s.reset();
+    s.update_bits([0], 3);
+    assert!(s.bytes_processed() == 0 && !s.is_fresh());
+}
+
+#[test]
+fn digest_progress_test() {
+    // Test that progress is reported up to the full length of the file
+    let data = vec![b'a'; 200_000];
+    let path = std::env::temp_dir().join("sha1-digest-progress-test");
+    std::fs::write(&path, &data).unwrap();
+
+    let mut reports = Vec::new();
+    let result = Sha1::digest_path_progress(&path, |done, total| reports.push((done, total)));
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(result.unwrap() == (known_good_hash(&data), 200_000));
+    assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(reports.iter().all(|&(_, total)| total == 200_000));
+    assert!(reports.last() == Some(&(200_000, 200_000)));
+}
+
+#[test]
+fn file_hasher_test() {
+    // Test that every buffer size produces the same hash
+    let data: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+
+    for &size in &[1, 63, 64, 65, 4096, 1 << 20] {
+        let hasher = FileHasher::new().buffer_size(size);
+        let (hash, bytes) = hasher.digest_reader(&data[..]).unwrap();
+        assert!(
+            hash == known_good_hash(&data) && bytes == 10_000,
+            "buffer size {}",
+            size
+        );
+    }
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_test() {
+    // Test the C interface through its Rust declarations
+    let data = b"Hello, world";
+    let expected = hash_to_bytes(&known_good_hash(data));
+    let mut out = [0; 20];
+
+    unsafe {
+        let ctx = ffi::sha1_new();
+        ffi::sha1_update(ctx, data.as_ptr(), 5);
+        ffi::sha1_update(ctx, std::ptr::null(), 0);
+        ffi::sha1_update(ctx, data[5..].as_ptr(), data.len() - 5);
+        ffi::sha1_finish(ctx, out.as_mut_ptr());
+        assert!(out == expected);
+
+        out = [0; 20];
+        ffi::sha1_oneshot(data.as_ptr(), data.len(), out.as_mut_ptr());
+        assert!(out == expected);
+
+        ffi::sha1_free(ffi::sha1_new());
+        ffi::sha1_free(std::ptr::null_mut());
+    }
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_header_test() {
+    // Test that include/sha1.h declares exactly the functions in ffi.rs, with the C types that
+    // cbindgen would give them, so that the header can't drift from the code unnoticed
+    fn c_type(rust: &str) -> &'static str {
+        match rust {
+            "*mut Sha1" => "sha1_ctx *",
+            "*const u8" => "const uint8_t *",
+            "*mut u8" => "uint8_t *",
+            "usize" => "size_t ",
+            _ => panic!("no C type for {} in ffi_header_test", rust),
+        }
+    }
+
+    let header = include_str!("../include/sha1.h");
+    let mut count = 0;
+
+    for line in include_str!("ffi.rs").lines() {
+        let sig = match line.split("extern \"C\" fn ").nth(1) {
+            Some(sig) => sig.trim_end_matches(" {"),
+            None => continue,
+        };
+
+        let (name, rest) = sig.split_at(sig.find('(').unwrap());
+        let (params, ret) = rest[1..].split_at(rest.find(')').unwrap() - 1);
+        let params: Vec<String> = params
+            .split(", ")
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let (param, ty) = p.split_at(p.find(": ").unwrap());
+                format!("{}{}", c_type(&ty[2..]), param)
+            })
+            .collect();
+        let params = if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.join(", ")
+        };
+        let ret = match ret.strip_prefix(") -> ") {
+            Some(ty) => c_type(ty),
+            None => "void ",
+        };
+
+        let prototype = format!("\n{}{}({});\n", ret, name, params);
+        assert!(
+            header.contains(&prototype),
+            "{:?} is not in sha1.h",
+            prototype
+        );
+        count += 1;
+    }
+
+    assert!(count == 5);
+    assert!(header.matches(");\n").count() == count);
+}
+
+#[test]
+fn sha0_vectors_test() {
+    // Test vectors from FIPS 180, plus a message spanning several chunks
+    let abc = hex::decode("0164b8a914cd2a5e74c4f7ff082c4d97f1edf880").unwrap();
+    let long = hex::decode("d2516ee1acfa5baf33dfc1c471e438449ef134c8").unwrap();
+    assert!(sha0::Sha0::digest(b"abc") == abc);
+    assert!(
+        sha0::Sha0::digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq") == long
+    );
+
+    let data = vec![b'a'; 1000];
+    let mut s = sha0::Sha0::new();
+    s.update(&data[..333]);
+    s.update(&data[333..]);
+    assert!(s.finish_reset() == sha0::Sha0::digest(&data));
+    assert!(s.finish() == sha0::Sha0::digest(b""));
+    assert!(sha0::Sha0::digest(&data) != Sha1::digest(&data));
+
+    // Test that padding is the same as SHA-1's around every chunk boundary, by hashing the
+    // padded message one block at a time
+    for len in 0..200 {
+        let mut s = sha0::Sha0::new();
+        for byte in &data[..len] {
+            s.update([*byte]);
+        }
+
+        let mut padded = data[..len].to_vec();
+        padded.extend(Sha1::padding(len as u64));
+        let mut expected = INITIAL_STATE;
+        for block in padded.chunks(64) {
+            expected = compress_portable::<true>(expected, block.try_into().unwrap());
+        }
+        assert!(
+            s.peek_finish() == expected && s.finish() == expected,
+            "{} x a",
+            len
+        );
+    }
+}
+
+#[test]
+fn hmac_sha1_96_test() {
+    // Test case 1 from RFC 2202, truncated as in RFC 2404
+    let mut h = hmac::Hmac::new([0x0b; 20]);
+    h.update(b"Hi There");
+    let tag = h.finish96();
+    assert!(tag.to_string() == "b617318655057264e28bc0b6");
+
+    let full = Digest::from(hmac::Hmac::mac([0x0b; 20], b"Hi There"));
+    assert!(full.truncate96() == tag);
+    assert!(tag.bytes()[..] == full.bytes()[..12]);
+}
+
+#[test]
+fn merkle_proofs_test() {
+    // Every leaf of trees with odd and even leaf counts has a valid proof, and nothing else does
+    let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+
+    for &leaf_len in &[1000, 500, 300, 100, 64, 7] {
+        let tree = merkle::MerkleTree::from_reader(&data[..], leaf_len).unwrap();
+        let root = tree.root();
+        let leaves: Vec<&[u8]> = data.chunks(leaf_len).collect();
+        assert!(tree.leaves().len() == leaves.len());
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(i).unwrap();
+            assert!(proof.verify(leaf, &root), "leaf {} of {}", i, leaf_len);
+            assert!(!proof.verify(&leaf[1..], &root));
+            assert!(!proof.verify(leaf, &Sha1::digest(leaf)));
+        }
+        assert!(tree.proof(leaves.len()).is_none());
+    }
+
+    let single = merkle::MerkleTree::from_reader(&b"abc"[..], 64).unwrap();
+    assert!(single.root() == merkle::leaf_hash(b"abc"));
+    let empty = merkle::MerkleTree::from_reader(&b""[..], 64).unwrap();
+    assert!(empty.root() == Sha1::digest(b"") && empty.proof(0).is_none());
+}
+
+#[test]
+fn resumable_file_hash_test() {
+    // A valid checkpoint is resumed from, a damaged one is ignored, and both are removed after
+    let path = std::env::temp_dir().join("sha1-resumable-test");
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7) as u8).collect();
+    std::fs::write(&path, &data).unwrap();
+
+    let hasher = ResumableFileHash::new(&path).interval(1000);
+    let checkpoint = std::env::temp_dir().join("sha1-resumable-test.sha1-checkpoint");
+    assert!(hasher.digest().unwrap() == (known_good_hash(&data), 100_000));
+    assert!(!checkpoint.exists());
+
+    // Checkpoint a different prefix, to tell whether the hash really resumed from it
+    let mut s = Sha1::new();
+    s.update([0; 4321]);
+    let meta = std::fs::metadata(&path).unwrap();
+    hasher.save_checkpoint(&meta, &s).unwrap();
+    s.update(&data[4321..]);
+    assert!(hasher.digest().unwrap() == (s.finish(), 100_000));
+    assert!(!checkpoint.exists());
+
+    std::fs::write(&checkpoint, b"not a checkpoint").unwrap();
+    assert!(hasher.digest().unwrap().0 == known_good_hash(&data));
+    assert!(!checkpoint.exists());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn digest_many_test() {
+    // Batch hashing matches hashing each item on its own
+    let items: Vec<Vec<u8>> = (0..200).map(|n| vec![b'k'; n]).collect();
+    let digests = Sha1::digest_many(&items);
+
+    assert!(digests.len() == items.len());
+    for (item, digest) in items.iter().zip(&digests) {
+        assert!(digest.words() == known_good_hash(item));
+    }
+    assert!(Sha1::digest_many(Vec::<&[u8]>::new()).is_empty());
+}
+
+#[test]
+fn update_iter_test() {
+    // Fragmented data hashes the same as the concatenation
+    let parts: [&[u8]; 4] = [b"header\r\n", b"", &[b'b'; 100], b"trailer"];
+    let mut s = Sha1::new();
+    s.update_iter(&parts);
+    assert!(s.finish() == known_good_hash(&parts.concat()));
+
+    let mut s = Sha1::new();
+    s.update_iter(vec![String::from("Hello, "), String::from("world")]);
+    assert!(s.finish() == known_good_hash(b"Hello, world"));
+}
+
+#[test]
+fn digest_conversions_test() {
+    // Words and bytes convert both ways in big-endian order
+    let words = [0x01020304, 0x05060708, 0x090a0b0c, 0x0d0e0f10, 0x11121314];
+    let bytes: [u8; 20] = Digest::from_words(words).into();
+    assert!(bytes == [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20]);
+
+    assert!(Hash::from(Digest::from(bytes)) == words);
+    assert!(Digest::from_bytes(bytes) == Digest::from(words));
+    assert!(Digest::from_bytes(bytes).words() == words);
+}
+
+#[test]
+fn digest_eq_hex_test() {
+    // Digests compare equal to their hex in either case, and unequal to anything else
+    let digest = Digest::from(Sha1::digest(b"Hello, world"));
+    assert_eq!(digest, "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8");
+    assert_eq!("E02AA1B106D5C7C6A98DEF2B13005D5B84FD8DC8", digest);
+    assert!(digest == *"e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8");
+
+    assert!(digest != "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc9");
+    assert!(digest != "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc");
+    assert!(digest != "");
+}
+
+#[test]
+fn snmp_keys_test() {
+    // Test vectors from RFC 3414 section A.3.2
+    let engine_id = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+    let key = snmp::password_to_key(b"maplesyrup");
+    assert!(Digest::from(key) == "9fb5cc0381497b3793528939ff788d5d79145211");
+
+    let local = snmp::localize_key(&key, &engine_id);
+    assert!(Digest::from(local) == "6695febc9288e36282235fc7151f128497b38f3f");
+}
+
+#[test]
+fn pbkdf2_vectors_test() {
+    // Test vectors from RFC 6070, and the WPA2 vector from IEEE 802.11i annex H.4
+    let tests: [(&[u8], &[u8], u32, &str); 4] = [
+        (
+            b"password",
+            b"salt",
+            1,
+            "0c60c80f961f0e71f3a9b524af6012062fe037a6",
+        ),
+        (
+            b"password",
+            b"salt",
+            2,
+            "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957",
+        ),
+        (
+            b"password",
+            b"salt",
+            4096,
+            "4b007901b765489abead49d926f721d065a429c1",
+        ),
+        (
+            b"passwordPASSWORDpassword",
+            b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+            4096,
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+        ),
+    ];
+
+    for &(password, salt, iterations, expected) in &tests {
+        let mut out = vec![0; expected.len() / 2];
+        pbkdf2::derive(password, salt, iterations, &mut out);
+        let out: String = out.iter().map(|b| format!("{:02x}", b)).collect();
+        assert!(out == expected);
+    }
+
+    let psk = pbkdf2::wpa2_psk("IEEE", "password");
+    let psk: String = psk.iter().map(|b| format!("{:02x}", b)).collect();
+    assert!(psk == "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e");
+}
+
+#[test]
+fn kbkdf_counter_mode_test() {
+    // Blocks are HMACs of [i] || label || 0x00 || context || [L], with 32 bit i and L
+    let mut out = [0; 50];
+    kbkdf::counter_mode(b"key", b"label", b"context", &mut out);
+
+    for i in 0..3u32 {
+        let mut h = hmac::Hmac::new(b"key");
+        h.update((i + 1).to_be_bytes());
+        h.update(b"label\0context");
+        h.update(400u32.to_be_bytes());
+        let block = Digest::from(h.finish()).bytes();
+
+        let start = i as usize * 20;
+        let end = (start + 20).min(50);
+        assert!(out[start..end] == block[..end - start]);
+    }
+
+    // The output length is part of the input, so a shorter key is not a prefix of a longer one
+    let mut short = [0; 20];
+    kbkdf::counter_mode(b"key", b"label", b"context", &mut short);
+    assert!(short[..] != out[..20]);
+}
+
+#[test]
+fn hash_chain_test() {
+    // Every link is the plain SHA-1 of the 20 bytes of the link before it
+    let links = chain::hash_chain_links(b"seed", 50);
+    assert!(links.len() == 50);
+    assert!(links[0] == known_good_hash(b"seed"));
+
+    for pair in links.windows(2) {
+        assert!(pair[1] == known_good_hash(&hash_to_bytes(&pair[0])));
+    }
+
+    assert!(chain::hash_chain(b"seed", 50) == links[49]);
+    assert!(chain::hash_chain(b"seed", 1) == links[0]);
+    assert!(chain::hash_chain_links(b"seed", 0).is_empty());
+}
+
+#[test]
+fn digest_const_test() {
+    // Compile-time hashing matches Sha1::digest() around the chunk and padding boundaries
+    const ABC: [u8; 20] = Sha1::digest_const(b"abc");
+    assert!(Digest::from(ABC) == "a9993e364706816aba3e25717850c26c9cd0d89d");
+
+    let data = [b'x'; 200];
+    for n in 0..data.len() {
+        assert!(Sha1::digest_const(&data[..n]) == hash_to_bytes(&known_good_hash(&data[..n])));
+    }
+}
+
+#[test]
+fn hash_macros_test() {
+    // The macros expand to the same bytes as hashing at runtime
+    const HELLO: [u8; 20] = sha1!("Hello, world");
+    assert!(HELLO == hash_to_bytes(&known_good_hash(b"Hello, world")));
+
+    let manifest = sha1_file!("../Cargo.toml");
+    assert!(manifest == hash_to_bytes(&Sha1::digest_path("Cargo.toml").unwrap().0));
+}
+
+#[test]
+fn sha1_file_large_test() {
+    // Test a file too big to hash within rustc's default const evaluation limit
+    let zeros = sha1_file!("../tests/fixtures/zeros-256k.bin");
+    assert!(zeros == hash_to_bytes(&known_good_hash(&[0; 256 * 1024])));
+}
+
+#[test]
+fn public_compress_test() {
+    // Compressing whole blocks by hand matches Sha1 for a message ending on a block boundary
+    let data: Vec<u8> = (0..192u32).map(|i| i as u8).collect();
+    let mut state = INITIAL_STATE;
+    for block in data.chunks(64).chain(Sha1::padding(192).chunks(64)) {
+        compress(&mut state, block.try_into().unwrap());
+    }
+    assert!(state == known_good_hash(&data));
+
+    // Length extension from a finished hash continues the same way
+    let mut s = Sha1::from_digest(Sha1::digest(&data), 192).unwrap();
+    s.update(&data[..64]);
+    let mut expected = known_good_hash(&data);
+    compress(&mut expected, data[..64].try_into().unwrap());
+    compress(&mut expected, Sha1::padding(320)[..].try_into().unwrap());
+    assert!(s.finish() == expected);
+}
+
+#[test]
+fn error_conversions_test() {
+    // I/O errors pass through Sha1Error unchanged, other errors become InvalidData
+    let missing = Sha1::digest_path("/nonexistent/sha1-error-test").unwrap_err();
+    assert!(matches!(&missing, Sha1Error::Io(e) if e.kind() == io::ErrorKind::NotFound));
+    assert!(std::error::Error::source(&missing).is_some());
+    assert!(io::Error::from(missing).kind() == io::ErrorKind::NotFound);
+
+    let bad_hex = hex::decode("xyz").unwrap_err();
+    assert!(bad_hex.to_string() == "hash is not 40 hex digits");
+    assert!(io::Error::from(bad_hex).kind() == io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn verifying_reader_test() {
+    // Matching data reads to the end, corrupted data fails at the end with the actual hash
+    let data = vec![b'v'; 10_000];
+    let expected = known_good_hash(&data);
+
+    let mut out = Vec::new();
+    let mut reader = VerifyingReader::new(&data[..], expected);
+    assert!(io::copy(&mut reader, &mut out).unwrap() == 10_000);
+    assert!(reader.verify().is_ok() && out == data);
+
+    let mut corrupted = data.clone();
+    corrupted[5000] = b'w';
+    let mut reader = VerifyingReader::new(&corrupted[..], expected);
+    let err = io::copy(&mut reader, &mut io::sink()).unwrap_err();
+    assert!(err.kind() == io::ErrorKind::InvalidData);
+    assert!(reader.bytes_read() == 10_000);
+
+    let actual = known_good_hash(&corrupted);
+    assert!(reader.verify() == Err(Sha1Error::Mismatch { expected, actual }));
+}
+
+#[test]
+fn verifying_writer_test() {
+    // finish() returns the inner writer only if the written data has the expected hash
+    let data = vec![b'u'; 10_000];
+    let expected = known_good_hash(&data);
+
+    let mut writer = VerifyingWriter::new(Vec::new(), expected);
+    writer.write_all(&data).unwrap();
+    assert!(writer.bytes_written() == 10_000);
+    assert!(writer.finish().unwrap() == data);
+
+    let mut writer = VerifyingWriter::new(Vec::new(), expected);
+    writer.write_all(&data[1..]).unwrap();
+    let actual = known_good_hash(&data[1..]);
+    assert!(writer.verify() == Err(Sha1Error::Mismatch { expected, actual }));
+    assert!(writer.finish().err() == Some(Sha1Error::Mismatch { expected, actual }));
+}
+
+#[test]
+fn sync_sha1_test() {
+    // Segments written by several threads taking turns hash the same as the whole stream
+    let hasher = SyncSha1::new();
+    let turn = std::sync::Mutex::new(0);
+    let segments: Vec<Vec<u8>> = (0..8).map(|i| vec![i as u8; 1000 + i]).collect();
+
+    std::thread::scope(|s| {
+        for (i, segment) in segments.iter().enumerate() {
+            let (hasher, turn) = (&hasher, &turn);
+            s.spawn(move || loop {
+                let mut t = turn.lock().unwrap();
+                if *t == i {
+                    (&*hasher).write_all(segment).unwrap();
+                    *t += 1;
+                    return;
+                }
+            });
+        }
+    });
+
+    assert!(hasher.bytes_processed() == segments.iter().map(|s| s.len() as u64).sum::<u64>());
+    assert!(hasher.peek_finish() == known_good_hash(&segments.concat()));
+    assert!(hasher.finish() == known_good_hash(&segments.concat()));
+}
+
+#[cfg(all(feature = "uring", target_os = "linux"))]
+#[test]
+fn digest_path_uring_test() {
+    // Test that reading with io_uring gives the same result as reading normally, for files that
+    // end at, before, and after the end of a read buffer
+    for &len in &[0, 100, 1 << 20, (5 << 20) + 12345] {
+        let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        let path = std::env::temp_dir().join(format!("sha1-uring-test-{}", len));
+        std::fs::write(&path, &data).unwrap();
+
+        let result = Sha1::digest_path_uring(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let (hash, bytes) = result.unwrap();
+        assert!(bytes == len as u64);
+        assert!(hash == known_good_hash(&data));
+    }
+
+    let missing = std::env::temp_dir().join("sha1-uring-test-missing");
+    assert!(matches!(
+        Sha1::digest_path_uring(missing),
+        Err(Sha1Error::Io(_))
+    ));
+}
+
+#[cfg(all(
+    feature = "asm",
+    target_arch = "x86_64",
+    target_feature = "sha",
+    target_feature = "sse4.1"
+))]
+#[test]
+fn asm_backend_test() {
+    // Test that the assembly backend matches the portable one, for states other than the initial
+    // state too
+    let mut h = INITIAL_STATE;
+    for seed in 0..64u8 {
+        let block: [u8; 64] = core::array::from_fn(|i| (i as u8).wrapping_mul(seed) ^ seed);
+        let next = asm::compress(h, &block);
+        assert!(next == compress_chunk::<false>(h, &block));
+        h = next;
+    }
+}
+
+#[cfg(feature = "small")]
+#[test]
+fn small_compress_test() {
+    // Test that the compact compression function matches the unrolled one for SHA-1 and SHA-0
+    let mut h = INITIAL_STATE;
+    for seed in 0..64u8 {
+        let block: [u8; 64] = core::array::from_fn(|i| (i as u8).wrapping_mul(seed) ^ seed);
+        assert!(compress_small::<true>(h, &block) == compress_chunk::<true>(h, &block));
+        let next = compress_small::<false>(h, &block);
+        assert!(next == compress_chunk::<false>(h, &block));
+        h = next;
+    }
+}
+
+#[cfg(feature = "openssl")]
+#[test]
+fn openssl_test() {
+    use openssl::OpenSslSha1;
+
+    // Test that OpenSSL's hashes match, however the data is split up, and that copies continue
+    // independently
+    let data: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+    for len in 0..data.len() {
+        assert!(OpenSslSha1::digest(&data[..len]) == Ok(known_good_hash(&data[..len])));
+    }
+
+    let mut s = OpenSslSha1::new().unwrap();
+    for piece in data.chunks(13) {
+        s.write_all(piece).unwrap();
+    }
+    let copy = s.try_clone().unwrap();
+    s.update(b"more").unwrap();
+    assert!(copy.finish() == Ok(known_good_hash(&data)));
+
+    let mut more = data.clone();
+    more.extend(b"more");
+    assert!(s.finish() == Ok(known_good_hash(&more)));
+
+    let e = Sha1Error::OpenSsl(0x0308010c);
+    assert!(e.to_string() == "OpenSSL error 0x308010c");
+}
+
+#[cfg(feature = "portable-simd")]
+#[test]
+fn portable_simd_schedule_test() {
+    // Test the std::simd schedule directly, since it is only selected on targets other than x86
+    // and ARM
+    for seed in 0..8u8 {
+        let block: [u8; 64] = core::array::from_fn(|i| (i as u8).wrapping_mul(seed) ^ seed);
+        let h = compress_scheduled(INITIAL_STATE, &block, simd::schedule);
+        assert!(h == compress_chunk::<false>(INITIAL_STATE, &block));
+    }
+}
+
+#[test]
+fn force_portable_test() {
+    // Test that forcing the portable backend switches to it and gives the same hashes
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let best = Sha1::active_backend();
+
+    Sha1::force_portable(true);
+    assert!(Sha1::active_backend() == Backend::Portable);
+    let portable = Sha1::digest(&data);
+
+    Sha1::force_portable(false);
+    assert!(Sha1::active_backend() == best);
+    assert!(Sha1::digest(&data) == portable);
+    assert!(portable == known_good_hash(&data));
+}
+
+#[test]
+fn offload_hasher_test() {
+    // Buffers hashed on the worker thread give the same hash as hashing them in order
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let hasher = OffloadHasher::with_capacity(1);
+    for chunk in data.chunks(777) {
+        let mut buf = chunk.to_vec();
+        while let Err(b) = hasher.try_update(buf) {
+            buf = b;
+            std::thread::yield_now();
+        }
+    }
+    assert!(hasher.finish() == known_good_hash(&data));
+
+    let hasher = OffloadHasher::new();
+    hasher.update(b"abc".to_vec());
+    let handle = hasher.close();
+    assert!(handle.wait() == known_good_hash(b"abc"));
+}
+
+#[test]
+fn mgf1_sha1_test() {
+    // Masks for short and multi-block lengths, checked against another MGF1 implementation
+    let tests: [(&[u8], &str); 3] = [
+        (b"foo", "1ac907"),
+        (b"foo", "1ac9075cd4"),
+        (
+            b"bar",
+            "bc0c655e016bc2931d85a2e675181adcef7f581f76df2739da74faac41627be2\
+             f7f415c89e983fd0ce80ced9878641cb4876",
+        ),
+    ];
+
+    for &(seed, expected) in &tests {
+        let mask = mgf1::mgf1_sha1(seed, expected.len() / 2);
+        let mask: String = mask.iter().map(|b| format!("{:02x}", b)).collect();
+        assert!(mask == expected);
+    }
+    assert!(mgf1::mgf1_sha1(b"bar", 0).is_empty());
+
+    // XORing the mask in twice gives back the data
+    let mut data = *b"some data block to be masked";
+    mgf1::mgf1_sha1_xor(b"seed", &mut data);
+    assert!(&data != b"some data block to be masked");
+    mgf1::mgf1_sha1_xor(b"seed", &mut data);
+    assert!(&data == b"some data block to be masked");
+}
+
+#[test]
+fn pkcs1_digest_info_test() {
+    // The encoding is the constant from RFC 8017 section 9.2 followed by the hash
+    let hash = Sha1::digest(b"abc");
+    let encoded = pkcs1::digest_info(&hash);
+    let encoded: String = encoded.iter().map(|b| format!("{:02x}", b)).collect();
+    assert!(encoded == "3021300906052b0e03021a05000414a9993e364706816aba3e25717850c26c9cd0d89d");
+
+    assert!(pkcs1::parse_digest_info(&pkcs1::digest_info(&hash)) == Some(hash));
+    assert!(pkcs1::parse_digest_info(&pkcs1::digest_info(&hash)[..34]).is_none());
+    assert!(pkcs1::parse_digest_info(&[0; 35]).is_none());
+}
+
+#[test]
+fn tls_prf_test() {
+    // P_SHA1 and the PRF checked against another implementation, with P_MD5 given as the output
+    // it would produce for the first half of the secret
+    let hex = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    };
+
+    let mut out = [0; 48];
+    tls_prf::p_sha1(b"secret!", b"client random server random", &mut out);
+    assert!(
+        hex(&out)
+            == "b8f444b7b8cd1355f750c0f67a8db02168252f3524ad08942dcfe1a2250712bb\
+                3c58149accc2129e406af2492a3086e4"
+    );
+
+    let p_md5 = |secret: &[u8], seed: &[u8], out: &mut [u8]| {
+        assert!(secret == b"secr");
+        assert!(seed == b"master secretclient random server random");
+        let md5 = "409cbeb3fbe7702f12ea1b782a4a82466dd10b10f42471df\
+                   3e42946a23e809fc5e6568b87acadafdd7fe92fae536d5e9";
+        for (i, b) in out.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&md5[i * 2..i * 2 + 2], 16).unwrap();
+        }
+    };
+    tls_prf::prf(
+        b"secret!",
+        b"master secret",
+        b"client random server random",
+        &mut out,
+        p_md5,
+    );
+    assert!(
+        hex(&out)
+            == "f2c0f27ded1901ce2ee3525425a04f1d53d571e1b23f74551b29f4947a5daad6\
+                49106d11874a2de03e61a2a3883a1b39"
+    );
+}
+
+#[test]
+fn digest_fingerprint_test() {
+    // Fingerprints are colon-separated uppercase pairs, and parse back in either case
+    let digest = Digest::from(Sha1::digest(b"abc"));
+    let fingerprint = digest.to_fingerprint();
+    assert!(fingerprint == "A9:99:3E:36:47:06:81:6A:BA:3E:25:71:78:50:C2:6C:9C:D0:D8:9D");
+    assert!(Digest::from_fingerprint(&fingerprint) == Ok(digest));
+    assert!(Digest::from_fingerprint(&fingerprint.to_lowercase()) == Ok(digest));
+
+    // Missing, extra, and malformed pairs are rejected
+    for bad in [
+        &fingerprint[..56],
+        &format!("{}:00", fingerprint),
+        &fingerprint.replace(':', ""),
+        &fingerprint.replacen("A9", "G9", 1),
+        &fingerprint.replacen("A9", "+9", 1),
+        "",
+    ] {
+        assert!(Digest::from_fingerprint(bad) == Err(Sha1Error::InvalidHex));
+    }
+}
+
+#[test]
+fn digest_as_key_test() {
+    // Digests order like their bytes and can be looked up by byte slices
+    use std::collections::{BTreeMap, HashSet};
+    use std::convert::TryFrom;
+
+    let a = Digest::from(Sha1::digest(b"a"));
+    let b = Digest::from(Sha1::digest(b"b"));
+    assert!((a < b) == (a.bytes() < b.bytes()));
+    assert!((a < b) == (a.to_hex() < b.to_hex()));
+
+    let mut map = BTreeMap::new();
+    map.insert(a, "a");
+    map.insert(b, "b");
+    assert!(map.get(&a.bytes()[..]) == Some(&"a"));
+
+    let set: HashSet<Digest> = [a, b].iter().copied().collect();
+    assert!(set.contains(b.as_ref()));
+
+    assert!(Digest::try_from(&a.bytes()[..]) == Ok(a));
+    assert!(Digest::try_from(&a.bytes()[..19]) == Err(Sha1Error::InvalidLength));
+    assert!(Digest::try_from(&[0; 21][..]) == Err(Sha1Error::InvalidLength));
+}
+
+#[test]
+fn hmac_key_test() {
+    // A processed key gives the same codes as processing the key for every message, for short
+    // keys and keys longer than a block
+    for key in [&b"key"[..], &[0xaa; 80]] {
+        let processed = hmac::HmacKey::new(key);
+        for len in [0, 20, 55, 56, 64, 200] {
+            let data = vec![0x5a; len];
+            assert!(processed.mac(&data) == hmac::Hmac::mac(key, &data));
+
+            let mut h = hmac::Hmac::from_key(&processed);
+            h.update(&data);
+            assert!(h.finish() == hmac::Hmac::mac(key, &data));
+        }
+    }
+}
+
+#[test]
+fn digest_file_range_test() {
+    // Ranges inside the file, running past its end, and starting past its end
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let path = std::env::temp_dir().join("sha1-digest-file-range-test");
+    std::fs::write(&path, &data).unwrap();
+    let mut file = std::fs::File::open(&path).unwrap();
+
+    let ranges = [(0, 10_000), (1234, 4321), (9_000, 5_000), (20_000, 10)];
+    let results: Vec<_> = ranges
+        .iter()
+        .map(|&(offset, len)| Sha1::digest_file_range(&mut file, offset, len).unwrap())
+        .collect();
+    std::fs::remove_file(&path).unwrap();
+
+    for (&(offset, len), &(hash, bytes)) in ranges.iter().zip(&results) {
+        let start = (offset as usize).min(data.len());
+        let end = (offset + len).min(data.len() as u64) as usize;
+        assert!(bytes == (end - start) as u64);
+        assert!(hash == known_good_hash(&data[start..end]));
+    }
+}
+
+#[test]
+fn dkim_body_hash_test() {
+    use dkim::Canonicalization::{Relaxed, Simple};
+
+    // The example from RFC 6376 section 3.4.5, with CRLF and bare LF line endings
+    for body in [&b" C \r\nD \t E\r\n\r\n\r\n"[..], b" C \nD \t E\n\n\n"] {
+        assert!(dkim::canonicalize_body(body, Simple) == b" C \r\nD \t E\r\n");
+        assert!(dkim::canonicalize_body(body, Relaxed) == b" C\r\nD E\r\n");
+        assert!(dkim::body_hash(body, Simple) == "CSbuGGcoeYJFyw+cZO2DPFHmfCo=");
+        assert!(dkim::body_hash(body, Relaxed) == "ekiYu+41TPsp6e+eqJHJcxAvAwk=");
+    }
+
+    // Empty bodies hash as a single line ending for simple, and as nothing for relaxed
+    for body in [&b""[..], b"\r\n", b"\r\n\r\n"] {
+        assert!(dkim::body_hash(body, Simple) == "uoq1oCgLlTqpdDX/iUbLy7J1Wic=");
+        assert!(dkim::body_hash(body, Relaxed) == "2jmj7l5rSw0yVb/vlWAYkK/YBwk=");
+    }
+    assert!(dkim::body_hash(b" \t\r\n", Relaxed) == "2jmj7l5rSw0yVb/vlWAYkK/YBwk=");
+
+    // A missing final line ending is added, and empty lines between content are kept
+    assert!(dkim::canonicalize_body(b"a\r\n\r\nb", Simple) == b"a\r\n\r\nb\r\n");
+}
+
+#[test]
+fn copy_and_hash_test() {
+    // Everything is copied and hashed, and write errors stop the copy
+    let data: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+    let mut out = Vec::new();
+    let (bytes, digest) = Sha1::copy_and_hash(&data[..], &mut out).unwrap();
+    assert!(out == data);
+    assert!(bytes == 300_000);
+    assert!(digest == Digest::from(known_good_hash(&data)));
+
+    let mut full = [0; 1000];
+    let result = Sha1::copy_and_hash(&data[..], &mut full[..]);
+    assert!(result.unwrap_err().kind() == io::ErrorKind::WriteZero);
+}
+
+#[test]
+fn streaming_hasher_test() {
+    // Generic code over StreamingHasher gives the same results as the inherent methods, also
+    // through a trait object
+    fn hash_twice<H: StreamingHasher>(mut hasher: H, data: &[u8]) -> (H::Output, H::Output) {
+        hasher.update(data);
+        let first = hasher.finish_reset();
+        hasher.update(b"garbage");
+        hasher.reset();
+        hasher.update(data);
+        (first, hasher.finish())
+    }
+
+    assert!(hash_twice(Sha1::new(), b"abc") == (Sha1::digest(b"abc"), Sha1::digest(b"abc")));
+    let sha0 = sha0::Sha0::digest(b"abc");
+    assert!(hash_twice(sha0::Sha0::new(), b"abc") == (sha0, sha0));
+    let checked = Sha1::digest(b"abc");
+    assert!(hash_twice(CheckedSha1::new(), b"abc") == (Ok(checked), Ok(checked)));
+
+    let mut hashers: Vec<Box<dyn StreamingHasher<Output = Hash>>> =
+        vec![Box::new(Sha1::new()), Box::new(sha0::Sha0::new())];
+    for h in &mut hashers {
+        h.update(b"abc");
+        assert!(h.output_len() == 20);
+    }
+    assert!(hashers[0].finish_reset() == Sha1::digest(b"abc"));
+    assert!(hashers[1].finish_reset() == sha0);
+}
+
+#[test]
+fn digest_parts_test() {
+    // Parts split anywhere, including empty parts, hash like the whole message
+    let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    for &(a, b) in &[(0, 0), (0, 1000), (63, 64), (100, 900), (1000, 1000)] {
+        let parts: [&[u8]; 3] = [&data[..a], &data[a..b], &data[b..]];
+        assert!(Sha1::digest_parts(parts) == known_good_hash(&data));
+        assert!(Sha1::digest_parts(&parts[..]) == known_good_hash(&data));
+    }
+    assert!(Sha1::digest_parts(Vec::<Vec<u8>>::new()) == known_good_hash(b""));
+}
+
+#[test]
+fn scram_sha1_test() {
+    // The example exchange from RFC 5802 section 5
+    let salt = [65, 37, 194, 71, 228, 58, 177, 233, 60, 109, 255, 118];
+    let auth_message = "n=user,r=fyko+d2lbbFgONRv9qkxdawL,\
+                        r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j,s=QSXCR+Q6sek8bf92,i=4096,\
+                        c=biws,r=fyko+d2lbbFgONRv9qkxdawL3rfcNHYJY1ZVvWVs7j";
+
+    let salted = scram::salted_password(b"pencil", salt, 4096);
+    let proof = scram::client_proof(&salted, auth_message);
+    assert!(base64::encode(&proof) == "v0X8v3Bz2T0CJGbJQyF0X+HI4Ts=");
+
+    let server_key = scram::server_key(&salted);
+    let signature = scram::server_signature(&server_key, auth_message);
+    assert!(base64::encode(&signature) == "rmF9pqV8S7suAoZWja4dJRkFsKQ=");
+    assert!(scram::verify_server_signature(
+        &server_key,
+        auth_message,
+        &signature
+    ));
+    assert!(!scram::verify_server_signature(
+        &server_key,
+        auth_message,
+        &proof
+    ));
+
+    let stored_key = scram::stored_key(&scram::client_key(&salted));
+    assert!(scram::verify_client_proof(
+        &stored_key,
+        auth_message,
+        &proof
+    ));
+    assert!(!scram::verify_client_proof(
+        &stored_key,
+        auth_message,
+        &signature
+    ));
+    assert!(!scram::verify_client_proof(
+        &stored_key,
+        auth_message,
+        &proof[..19]
+    ));
+}
+
+#[test]
+fn compare_streams_test() {
+    // The first differing window is found, including when one stream is a prefix of the other
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let mut changed = data.clone();
+    changed[5000] ^= 1;
+    changed[9000] ^= 1;
+
+    assert!(pieces::compare_streams(&data[..], &data[..], 1024)
+        .unwrap()
+        .is_none());
+    assert!(pieces::compare_streams(&b""[..], &b""[..], 1024)
+        .unwrap()
+        .is_none());
+
+    let d = pieces::compare_streams(&data[..], &changed[..], 1024)
+        .unwrap()
+        .unwrap();
+    assert!(d.offset == 4096);
+    assert!(d.left == known_good_hash(&data[4096..5120]));
+    assert!(d.right == known_good_hash(&changed[4096..5120]));
+
+    let d = pieces::compare_streams(&data[..8192], &data[..], 1024)
+        .unwrap()
+        .unwrap();
+    assert!(d.offset == 8192);
+    assert!(d.left == known_good_hash(b""));
+}
+
+#[cfg(feature = "cas")]
+#[test]
+fn cas_store_test() {
+    use cas::Store;
+
+    // Test that blobs round trip through the fan-out layout, corruption is caught, and gc removes
+    // only the blobs it is told to
+    let root = std::env::temp_dir().join("sha1-cas-test");
+    let _ = std::fs::remove_dir_all(&root);
+    let store = Store::open(&root).unwrap();
+
+    let a = store.put(b"Hello, world").unwrap();
+    let b = store.put_reader(&b"other blob"[..]).unwrap();
+    assert!(a.words() == known_good_hash(b"Hello, world"));
+    assert!(b.words() == known_good_hash(b"other blob"));
+    assert!(
+        store.path(&a)
+            == root
+                .join("e0")
+                .join("2aa1b106d5c7c6a98def2b13005d5b84fd8dc8")
+    );
+    assert!(store.put(b"Hello, world").unwrap() == a);
+    assert!(store.contains(&a) && store.contains(&b));
+    assert!(store.get(&a).unwrap() == b"Hello, world");
+
+    std::fs::write(store.path(&b), b"corrupted").unwrap();
+    assert!(matches!(store.get(&b), Err(Sha1Error::Mismatch { .. })));
+
+    assert!(store.gc(|d| *d == a).unwrap() == 1);
+    let missing = store.get(&b);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    assert!(matches!(missing, Err(Sha1Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound));
+}
+
+#[test]
+fn find_duplicates_test() {
+    use dir::{find_duplicate_files, find_duplicates, DirOptions};
+
+    // Test that only files with the same contents are grouped, not those merely of the same size
+    let root = std::env::temp_dir().join("sha1-find-duplicates-test");
+    std::fs::create_dir_all(root.join("sub")).unwrap();
+    std::fs::write(root.join("a"), b"same").unwrap();
+    std::fs::write(root.join("b"), b"diff").unwrap();
+    std::fs::write(root.join("c"), b"unique size").unwrap();
+    std::fs::write(root.join("sub/d"), b"same").unwrap();
+    std::fs::write(root.join("e"), b"").unwrap();
+    std::fs::write(root.join("sub/f"), b"").unwrap();
+
+    let groups = find_duplicates(&root, &DirOptions::new()).unwrap();
+    let listed = find_duplicate_files(vec![root.join("a"), root.join("b"), root.join("a")]);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let same = vec![root.join("a"), root.join("sub/d")];
+    let empty = vec![root.join("e"), root.join("sub/f")];
+    assert!(groups == vec![same, empty]);
+    assert!(listed.unwrap().is_empty());
+}
+
+#[test]
+fn self_test() {
+    // Test that the known-answer tests pass on the active backend
+    assert!(Sha1::self_test().is_ok());
+
+    let e = SelfTestError {
+        vector: "TEST1",
+        expected: known_good_hash(b"abc"),
+        actual: known_good_hash(b""),
+    };
+    assert!(
+        e.to_string()
+            == "self-test TEST1 failed: expected a9993e364706816aba3e25717850c26c9cd0d89d, \
+                got da39a3ee5e6b4b0d3255bfef95601890afd80709"
+    );
+}
+
+#[test]
+fn utf16le_test() {
+    // Test strings with surrogate pairs, and long enough to take several pieces
+    for s in [
+        "",
+        "Hello, world",
+        "\u{1f600} caf\u{e9} \u{4e2d}",
+        &"x\u{10437}".repeat(100),
+    ] {
+        let bytes: Vec<u8> = s.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert!(Sha1::digest_utf16le(s) == known_good_hash(&bytes));
+    }
+}
+
+#[test]
+fn digest_bucket_test() {
+    // Test that buckets are taken from the leading bits, and that they spread evenly
+    let digest = Digest::from_bytes([0x80; 20]);
+    assert!(digest.to_u64() == 0x8080808080808080);
+    assert!(digest.bucket(1) == 0);
+    assert!(digest.bucket(2) == 1);
+    assert!(digest.bucket(256) == 0x80);
+    assert!(Digest::from_bytes([0xff; 20]).bucket(u64::MAX) == u64::MAX - 1);
+
+    let mut counts = [0; 7];
+    for i in 0..7000u32 {
+        counts[Digest::from(Sha1::digest(i.to_le_bytes())).bucket(7) as usize] += 1;
+    }
+    assert!(counts.iter().all(|&c| (850..1150).contains(&c)));
+}
+
+#[test]
+fn hash_drbg_test() {
+    use drbg::HashDrbg;
+
+    // Test against an independent implementation of SP 800-90A, with additional input and a
+    // reseed, and that the reseed interval is enforced
+    let hex = |b: &[u8]| b.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    let entropy: Vec<u8> = (0..32).collect();
+    let nonce: Vec<u8> = (100..108).collect();
+    let mut drbg = HashDrbg::new(&entropy, &nonce, b"personal");
+    drbg.set_reseed_interval(2);
+
+    let mut out = [0; 30];
+    drbg.generate(&mut out, b"").unwrap();
+    assert!(hex(&out) == "10ae39b760e7043be5ef0e7a360591109dd520393b5a6c1635fb788499ea");
+
+    let mut out = [0; 45];
+    drbg.generate(&mut out, b"additional").unwrap();
+    assert!(
+        hex(&out)
+            == "a6f936211fb3b1f55dfa5f491501e32012ef906cc2523a328a0b8bc90d604ffa37457\
+                98f27ddb90b8739c3717d"
+    );
+
+    let mut out = [0; 20];
+    assert!(drbg.remaining_before_reseed() == 0);
+    assert!(drbg.generate(&mut out, b"") == Err(Sha1Error::ReseedRequired));
+
+    let entropy: Vec<u8> = (200..232).collect();
+    drbg.reseed(&entropy, b"more");
+    drbg.generate(&mut out, b"").unwrap();
+    assert!(hex(&out) == "4c8e7130714869366d705c98e5e5488089bebeed");
+}
+
+#[cfg(feature = "readahead")]
+#[test]
+fn read_ahead_test() {
+    use std::io::{Seek, SeekFrom};
+
+    // Test that hints don't change what is read, including from the middle of a file
+    let path = std::env::temp_dir().join("sha1-read-ahead-test");
+    let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&path, &data).unwrap();
+
+    let mut file = std::fs::File::open(&path).unwrap();
+    file.seek(SeekFrom::Start(1000)).unwrap();
+    let hinted = FileHasher::new().digest_file(&mut file).unwrap();
+    let range = FileHasher::new()
+        .digest_file_range(&mut file, 50, 70_000)
+        .unwrap();
+    let unhinted = FileHasher::new()
+        .read_ahead(false)
+        .digest_path(&path)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(hinted == (known_good_hash(&data[1000..]), 99_000));
+    assert!(range == (known_good_hash(&data[50..70_050]), 70_000));
+    assert!(unhinted == (known_good_hash(&data), 100_000));
+}
+
+#[test]
+fn update_from_reader_test() {
+    // Test hashing framed sections of one stream without reading past each section
+    let data: Vec<u8> = (0..30_000u32).map(|i| (i % 251) as u8).collect();
+    let mut reader = &data[..];
+
+    let mut first = Sha1::new();
+    assert!(first.update_from_reader(&mut reader, Some(10_000)).unwrap() == 10_000);
+    let mut skipped = [0; 4];
+    reader.read_exact(&mut skipped).unwrap();
+    let mut rest = Sha1::new();
+    assert!(rest.update_from_reader(&mut reader, None).unwrap() == 19_996);
+    let mut empty = Sha1::new();
+    assert!(empty.update_from_reader(&mut reader, Some(5)).unwrap() == 0);
+
+    assert!(first.finish() == known_good_hash(&data[..10_000]));
+    assert!(rest.finish() == known_good_hash(&data[10_004..]));
+    assert!(empty.finish() == known_good_hash(b""));
+}
+
+#[test]
+fn cancel_flag_test() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    // Reader that sets the flag once it has been read from three times
+    struct StopAfterThree<'a> {
+        data: &'a [u8],
+        stop: &'a AtomicBool,
+        reads: u32,
+    }
+
+    impl Read for StopAfterThree<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads += 1;
+            if self.reads == 3 {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+            self.data.read(buf)
+        }
+    }
+
+    // Test that setting the flag stops hashing between reads, and that the partial hash can be
+    // continued to the full hash
+    let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+    let stop = Arc::new(AtomicBool::new(false));
+    let hasher = FileHasher::new()
+        .buffer_size(1000)
+        .cancel_flag(stop.clone());
+
+    let reader = StopAfterThree {
+        data: &data,
+        stop: &stop,
+        reads: 0,
+    };
+    let mut partial = match hasher.digest_reader(reader) {
+        Err(Sha1Error::Cancelled(s)) => *s,
+        _ => panic!("hashing was not cancelled"),
+    };
+    assert!(partial.bytes_processed() == 3000);
+    partial.update(&data[3000..]);
+    assert!(partial.finish() == known_good_hash(&data));
+
+    stop.store(false, Ordering::Relaxed);
+    assert!(hasher.digest_reader(&data[..]).unwrap() == (known_good_hash(&data), 10_000));
+}
+
+#[test]
+fn checked_sha1_test() {
+    // Test that both implementations agree with Sha1 around every block boundary, whichever
+    // piece sizes the data is added in
+    let data: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+    for len in 0..data.len() {
+        let expected = known_good_hash(&data[..len]);
+        assert!(CheckedSha1::digest(&data[..len]) == Ok(expected));
+
+        let mut hasher = CheckedSha1::new();
+        for piece in data[..len].chunks(13) {
+            hasher.write_all(piece).unwrap();
+        }
+        assert!(hasher.bytes_processed() == len as u64);
+        assert!(hasher.finish() == Ok(expected));
+    }
+
+    let e = Sha1Error::Corrupted { offset: 128 };
+    assert!(e.to_string() == "hash computation disagreed at byte 128");
+}
+
+#[test]
+fn multipart_hasher_test() {
+    // Test that parts are split at the same boundaries however the data is added, and that the
+    // whole hash covers everything
+    let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    let expected: Vec<Hash> = data.chunks(100).map(known_good_hash).collect();
+
+    for piece_len in &[1, 7, 100, 333, 1000] {
+        let mut hasher = pieces::MultipartHasher::new(100);
+        for piece in data.chunks(*piece_len) {
+            hasher.write_all(piece).unwrap();
+        }
+        assert!(hasher.parts().len() == 10);
+        assert!(hasher.bytes_processed() == 1000);
+        assert!(hasher.finish() == (expected.clone(), known_good_hash(&data)));
+    }
+
+    let mut hasher = pieces::MultipartHasher::new(300);
+    hasher.update(&data);
+    let (parts, whole) = hasher.finish();
+    assert!(parts == data.chunks(300).map(known_good_hash).collect::<Vec<_>>());
+    assert!(parts.len() == 4 && whole == known_good_hash(&data));
+
+    assert!(pieces::MultipartHasher::new(100).finish() == (Vec::new(), known_good_hash(b"")));
+}
+
+#[test]
+fn process_budget_test() {
+    // Test that each call does at most the given number of blocks of work and that the whole
+    // buffer still hashes correctly, starting from a partly filled chunk
+    let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+
+    for &budget in &[0, 1, 3, 100] {
+        let mut s = Sha1::new();
+        s.update(&data[..10]);
+        let mut rest = &data[10..];
+
+        let mut blocks = s.bytes_processed() / 64;
+        while !rest.is_empty() {
+            let before = rest.len();
+            let n = s.process_budget(&mut rest, budget);
+            assert!(n == before - rest.len());
+            assert!(s.bytes_processed() / 64 - blocks <= budget as u64);
+            blocks = s.bytes_processed() / 64;
+
+            if budget == 0 {
+                break;
+            }
+        }
+
+        if budget == 0 {
+            assert!(rest.len() == data.len() - 63);
+        } else {
+            assert!(s.finish() == known_good_hash(&data));
+        }
+    }
+}
+
+#[test]
+fn manifest_verify_parallel_test() {
+    use manifest::{Entry, Format, Manifest, Summary};
+
+    // Test that every entry is reported exactly once with the same status as serial
+    // verification, whatever the number of threads
+    let dir = std::env::temp_dir().join("sha1-manifest-parallel-test");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut manifest = Manifest::default();
+    for i in 0..50 {
+        let data = format!("file {}", i);
+        std::fs::write(dir.join(i.to_string()), &data).unwrap();
+
+        // Every fifth entry has the wrong hash and every seventh file is missing
+        let hash = known_good_hash(if i % 5 == 0 {
+            b"wrong"
+        } else {
+            data.as_bytes()
+        });
+        manifest.entries.push(Entry {
+            hash,
+            path: if i % 7 == 0 {
+                format!("gone{}", i)
+            } else {
+                i.to_string()
+            },
+            format: Format::Gnu,
+        });
+    }
+
+    let serial = manifest.verify(&dir);
+    for &threads in &[0, 1, 4, 100] {
+        let mut seen = vec![false; manifest.entries.len()];
+        let summary = manifest.verify_parallel(&dir, threads, |entry, status| {
+            let i = manifest.entries.iter().position(|e| e == entry).unwrap();
+            assert!(!seen[i] && *status == serial[i].1);
+            seen[i] = true;
+        });
+
+        assert!(seen.iter().all(|&s| s));
+        let expected = Summary {
+            ok: 34,
+            mismatched: 8,
+            missing: 8,
+            errors: 0,
+        };
+        assert!(summary == expected && summary.total() == 50 && !summary.all_ok());
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    let summary = Manifest::default().verify_parallel(&dir, 4, |_, _| panic!("no entries"));
+    assert!(summary.total() == 0 && summary.all_ok());
+}
+
+#[test]
+fn aggregate_digest_test() {
+    // Test that the fingerprint doesn't depend on order, and that removing items and merging
+    // collections give the same fingerprint as building the collection directly
+    let items: Vec<String> = (0..20).map(|i| format!("item {}", i)).collect();
+    let forward: AggregateDigest = items.iter().collect();
+    let backward: AggregateDigest = items.iter().rev().collect();
+    assert!(forward == backward && forward.len() == 20);
+
+    let mut partial: AggregateDigest = items[..10].iter().collect();
+    partial.merge(&items[10..].iter().collect());
+    assert!(partial == forward);
+
+    partial.insert("extra");
+    partial.remove("extra");
+    for item in &items {
+        partial.remove(item);
+    }
+    assert!(partial == AggregateDigest::new() && partial.is_empty());
+    assert!(partial.digest() == Digest::from([0; 20]));
+
+    // Test that the sum carries between words and wraps around at 2^160
+    let mut sum = AggregateDigest::new();
+    sum.insert_digest([0, 0, 0, 0, 0xffffffff]);
+    sum.insert_digest([0, 0, 0, 0, 1]);
+    assert!(sum.digest().words() == [0, 0, 0, 1, 0]);
+    sum.insert_digest([0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0]);
+    assert!(sum.digest().words() == [0; 5] && sum.len() == 3);
+
+    let single: AggregateDigest = [b"abc"].iter().collect();
+    assert!(single.digest() == Digest::from(known_good_hash(b"abc")));
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn python_test() {
+    // Test the Python module through the interpreter, the way Python code would use it
+    use pyo3::prelude::*;
+
+    Python::initialize();
+    Python::attach(|py| {
+        let sha1 = pyo3::wrap_pymodule!(crate::python::init)(py);
+        let dir = std::env::temp_dir().join(format!("sha1-python-test-{}", std::process::id()));
+        std::fs::write(&dir, b"Hello, world").unwrap();
+        let path = dir.to_str().unwrap();
+
+        pyo3::py_run!(
+            py,
+            sha1 path,
+            r#"
+            expected = "e02aa1b106d5c7c6a98def2b13005d5b84fd8dc8"
+            assert sha1.hexdigest(b"Hello, world") == expected
+            assert sha1.digest(b"Hello, world") == bytes.fromhex(expected)
+            assert sha1.hexdigest_path(path) == expected
+
+            h = sha1.Sha1(b"Hello, ")
+            c = h.copy()
+            h.update(b"world")
+            assert h.hexdigest() == expected
+            assert h.digest() == bytes.fromhex(expected)
+            assert c.hexdigest() == sha1.hexdigest(b"Hello, ")
+            assert sha1.Sha1().hexdigest() == sha1.hexdigest(b"")
+            assert (h.name, h.digest_size, h.block_size) == ("sha1", 20, 64)
+
+            try:
+                sha1.hexdigest_path(path + ".missing")
+                assert False
+            except FileNotFoundError:
+                pass
+            "#
+        );
+
+        std::fs::remove_file(&dir).unwrap();
+    });
+}
+
 fn known_good_hash(data: &[u8]) -> Hash {
     let bytes: [u8; 20] = mitsuhiko::Sha1::from(data).digest().bytes();
 
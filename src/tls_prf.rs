@@ -0,0 +1,60 @@
+//! The pseudo-random function of TLS 1.0 and 1.1 (RFC 2246 section 5, RFC 4346 section 5).
+//!
+//! The PRF splits the secret in two halves, expands one with HMAC-MD5 and the other with
+//! HMAC-SHA1, and XORs the results. This crate only provides SHA-1, so the MD5 half is passed in
+//! by the caller as a function computing `P_MD5`.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::tls_prf;
+//! # fn p_md5(secret: &[u8], seed: &[u8], out: &mut [u8]) {}
+//! # let (pre_master_secret, client_random, server_random) = ([0; 48], [0; 32], [0; 32]);
+//! let mut master_secret = [0; 48];
+//! let seed = [client_random, server_random].concat();
+//! tls_prf::prf(&pre_master_secret, b"master secret", &seed, &mut master_secret, p_md5);
+//! ```
+
+use crate::hash_to_bytes;
+use crate::hmac::Hmac;
+
+/// Fills `out` with the `P_SHA1` data expansion of `secret` and `seed`.
+///
+/// Each 20 byte block is `HMAC(secret, A(i) + seed)`, where `A(0)` is the seed and `A(i)` is
+/// `HMAC(secret, A(i - 1))`.
+pub fn p_sha1<K: AsRef<[u8]>, S: AsRef<[u8]>>(secret: K, seed: S, out: &mut [u8]) {
+    let seed = seed.as_ref();
+    let key = Hmac::new(secret);
+    let mac = |parts: &[&[u8]]| {
+        let mut h = key.clone();
+        parts.iter().for_each(|part| h.update(part));
+        hash_to_bytes(&h.finish())
+    };
+
+    let mut a = mac(&[seed]);
+    for block in out.chunks_mut(20) {
+        block.copy_from_slice(&mac(&[&a, seed])[..block.len()]);
+        a = mac(&[&a]);
+    }
+}
+
+/// Fills `out` with the TLS 1.0/1.1 PRF of `secret`, `label`, and `seed`.
+///
+/// `p_md5(secret_half, label_and_seed, out)` must fill `out` with the `P_MD5` data expansion,
+/// which is the same as `p_sha1()` with HMAC-MD5 instead of HMAC-SHA1.
+pub fn prf<F>(secret: &[u8], label: &[u8], seed: &[u8], out: &mut [u8], p_md5: F)
+where
+    F: FnOnce(&[u8], &[u8], &mut [u8]),
+{
+    // Each half is rounded up, so they share the middle byte of an odd length secret
+    let half = secret.len().div_ceil(2);
+    let (s1, s2) = (&secret[..half], &secret[secret.len() - half..]);
+    let label_seed = [label, seed].concat();
+
+    p_md5(s1, &label_seed, out);
+
+    let mut sha = vec![0; out.len()];
+    p_sha1(s2, &label_seed, &mut sha);
+    for (o, s) in out.iter_mut().zip(sha) {
+        *o ^= s;
+    }
+}
@@ -0,0 +1,109 @@
+//! BitTorrent v1 helpers.
+//!
+//! Example usage:
+//! ```
+//! # use sha1::torrent;
+//! # fn f(torrent_file: &[u8]) -> std::io::Result<()> {
+//! // Build the "pieces" value of an info dictionary for a file with 256 KiB pieces
+//! let pieces = torrent::piece_hashes_path("foo.iso", 256 * 1024)?;
+//!
+//! // Find the info-hash of an existing .torrent file
+//! let info_hash = torrent::find_info(torrent_file).map(torrent::info_hash);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{hash_to_bytes, pieces, Sha1, Sha1Error};
+use std::io::Read;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::path::Path;
+
+/// Hashes everything from a reader in pieces of `piece_len` bytes and returns the concatenated
+/// 20 byte piece hashes, which is the value of the `pieces` key of an info dictionary.
+///
+/// Panics if `piece_len` is 0.
+pub fn piece_hashes<R: Read>(reader: R, piece_len: usize) -> Result<Vec<u8>, Sha1Error> {
+    let hashes = pieces::digest_reader(reader, piece_len)?;
+    Ok(hashes.iter().flat_map(hash_to_bytes).collect())
+}
+
+/// Hashes a whole file by its path in pieces of `piece_len` bytes. See
+/// [`piece_hashes`](fn.piece_hashes.html).
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn piece_hashes_path<P>(path: P, piece_len: usize) -> Result<Vec<u8>, Sha1Error>
+where
+    P: AsRef<Path>,
+{
+    let hashes = pieces::digest_path(path, piece_len)?;
+    Ok(hashes.iter().flat_map(hash_to_bytes).collect())
+}
+
+/// Returns the info-hash of a torrent, given its bencoded info dictionary exactly as it appears
+/// in the .torrent file.
+pub fn info_hash(info: &[u8]) -> [u8; 20] {
+    hash_to_bytes(&Sha1::digest(info))
+}
+
+/// Finds the bencoded info dictionary in the contents of a .torrent file. Returns `None` if the
+/// file is not a bencoded dictionary or has no `info` key.
+pub fn find_info(torrent: &[u8]) -> Option<&[u8]> {
+    if torrent.first() != Some(&b'd') {
+        return None;
+    }
+
+    let mut pos = 1;
+    while *torrent.get(pos)? != b'e' {
+        let key_end = skip_value(torrent, pos)?;
+        let value_end = skip_value(torrent, key_end)?;
+
+        if torrent.get(pos..key_end)? == b"4:info" {
+            return Some(&torrent[key_end..value_end]);
+        }
+
+        pos = value_end;
+    }
+
+    None
+}
+
+// Returns the position just past the bencoded value starting at pos. Nested lists and
+// dictionaries are skipped with a counter rather than recursion, so that a deeply nested file
+// can't overflow the stack.
+fn skip_value(data: &[u8], mut pos: usize) -> Option<usize> {
+    // Number of lists and dictionaries entered but not yet ended
+    let mut depth = 0usize;
+
+    loop {
+        match *data.get(pos)? {
+            // Integer: i<digits>e
+            b'i' => pos += data[pos..].iter().position(|&b| b == b'e')? + 1,
+
+            // List or dictionary: l<values>e or d<keys and values>e
+            b'l' | b'd' => {
+                depth += 1;
+                pos += 1;
+            }
+            b'e' if depth > 0 => {
+                depth -= 1;
+                pos += 1;
+            }
+
+            // Byte string: <length>:<bytes>
+            b'0'..=b'9' => {
+                let colon = pos + data[pos..].iter().position(|&b| b == b':')?;
+                let len: usize = std::str::from_utf8(&data[pos..colon]).ok()?.parse().ok()?;
+                pos = colon.checked_add(1)?.checked_add(len)?;
+                if pos > data.len() {
+                    return None;
+                }
+            }
+
+            _ => return None,
+        }
+
+        if depth == 0 {
+            return Some(pos);
+        }
+    }
+}
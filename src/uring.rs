@@ -0,0 +1,380 @@
+// File hashing with io_uring, so that reading the next parts of a file overlaps with hashing the
+// parts already read. The ring is set up with raw system calls, using the definitions from
+// linux/io_uring.h.
+
+use crate::{FileHasher, Hash, Sha1, Sha1Error};
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// Number of reads kept in flight, and the size of each of their buffers
+const DEPTH: usize = 4;
+const BUF_LEN: usize = 1 << 20;
+
+const IORING_OP_READ_FIXED: u8 = 4;
+const IORING_OP_READ: u8 = 22;
+const IORING_ENTER_GETEVENTS: u32 = 1;
+const IORING_REGISTER_BUFFERS: u32 = 0;
+const IORING_FEAT_SINGLE_MMAP: u32 = 1;
+const IORING_OFF_SQ_RING: libc::off_t = 0;
+const IORING_OFF_CQ_RING: libc::off_t = 0x8000000;
+const IORING_OFF_SQES: libc::off_t = 0x10000000;
+
+#[repr(C)]
+#[derive(Default)]
+struct SqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct CqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Params {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqringOffsets,
+    cq_off: CqringOffsets,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Sqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    rw_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    addr3: u64,
+    pad: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Cqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+// Submission and completion rings shared with the kernel
+struct Ring {
+    fd: libc::c_int,
+    maps: Vec<(*mut libc::c_void, usize)>,
+    sqes: *mut Sqe,
+    sq_tail: *const AtomicU32,
+    sq_mask: u32,
+    sq_array: *mut u32,
+    cq_head: *const AtomicU32,
+    cq_tail: *const AtomicU32,
+    cq_mask: u32,
+    cqes: *const Cqe,
+    to_submit: u32,
+    in_flight: usize,
+}
+
+impl Ring {
+    fn new(entries: u32) -> io::Result<Ring> {
+        let mut p = Params::default();
+        let fd = unsafe { libc::syscall(libc::SYS_io_uring_setup, entries, &mut p as *mut Params) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut ring = Ring {
+            fd: fd as libc::c_int,
+            maps: Vec::new(),
+            sqes: ptr::null_mut(),
+            sq_tail: ptr::null(),
+            sq_mask: 0,
+            sq_array: ptr::null_mut(),
+            cq_head: ptr::null(),
+            cq_tail: ptr::null(),
+            cq_mask: 0,
+            cqes: ptr::null(),
+            to_submit: 0,
+            in_flight: 0,
+        };
+
+        let mut sq_len = p.sq_off.array as usize + p.sq_entries as usize * 4;
+        let mut cq_len = p.cq_off.cqes as usize + p.cq_entries as usize * 16;
+        let single = p.features & IORING_FEAT_SINGLE_MMAP != 0;
+        if single {
+            sq_len = sq_len.max(cq_len);
+            cq_len = sq_len;
+        }
+
+        // Safety: the offsets come from the kernel and lie within the mapped lengths
+        unsafe {
+            let sq = ring.map(sq_len, IORING_OFF_SQ_RING)? as *mut u8;
+            let cq = if single {
+                sq
+            } else {
+                ring.map(cq_len, IORING_OFF_CQ_RING)? as *mut u8
+            };
+            let sqes_len = p.sq_entries as usize * std::mem::size_of::<Sqe>();
+            ring.sqes = ring.map(sqes_len, IORING_OFF_SQES)? as *mut Sqe;
+
+            ring.sq_tail = sq.add(p.sq_off.tail as usize) as *const AtomicU32;
+            ring.sq_mask = *(sq.add(p.sq_off.ring_mask as usize) as *const u32);
+            ring.sq_array = sq.add(p.sq_off.array as usize) as *mut u32;
+            ring.cq_head = cq.add(p.cq_off.head as usize) as *const AtomicU32;
+            ring.cq_tail = cq.add(p.cq_off.tail as usize) as *const AtomicU32;
+            ring.cq_mask = *(cq.add(p.cq_off.ring_mask as usize) as *const u32);
+            ring.cqes = cq.add(p.cq_off.cqes as usize) as *const Cqe;
+        }
+
+        Ok(ring)
+    }
+
+    // Maps one of the ring's regions. Mappings are undone when the ring is dropped.
+    unsafe fn map(&mut self, len: usize, offset: libc::off_t) -> io::Result<*mut libc::c_void> {
+        let prot = libc::PROT_READ | libc::PROT_WRITE;
+        let flags = libc::MAP_SHARED | libc::MAP_POPULATE;
+        let ptr = libc::mmap(ptr::null_mut(), len, prot, flags, self.fd, offset);
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        self.maps.push((ptr, len));
+        Ok(ptr)
+    }
+
+    // Registers each BUF_LEN part of buf as a fixed buffer, so that the kernel doesn't have to
+    // map them for every read.
+    fn register_buffers(&mut self, buf: *mut u8) -> io::Result<()> {
+        let iovecs: Vec<libc::iovec> = (0..DEPTH)
+            .map(|i| libc::iovec {
+                iov_base: unsafe { buf.add(i * BUF_LEN) } as *mut libc::c_void,
+                iov_len: BUF_LEN,
+            })
+            .collect();
+
+        let ret = unsafe {
+            libc::syscall(
+                libc::SYS_io_uring_register,
+                self.fd,
+                IORING_REGISTER_BUFFERS,
+                iovecs.as_ptr(),
+                DEPTH as u32,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    // Queues a request. It is submitted by the next call to wait().
+    fn push(&mut self, sqe: Sqe) {
+        // Never more than DEPTH requests are in flight, so there is always a free entry
+        debug_assert!(self.in_flight < DEPTH);
+
+        // Safety: only this side writes the tail, and i is within the ring
+        unsafe {
+            let tail = (*self.sq_tail).load(Ordering::Relaxed);
+            let i = tail & self.sq_mask;
+            ptr::write(self.sqes.add(i as usize), sqe);
+            *self.sq_array.add(i as usize) = i;
+            (*self.sq_tail).store(tail.wrapping_add(1), Ordering::Release);
+        }
+
+        self.to_submit += 1;
+        self.in_flight += 1;
+    }
+
+    // Submits queued requests and waits for a completion.
+    fn wait(&mut self) -> io::Result<Cqe> {
+        loop {
+            // Safety: only this side writes the head, and the kernel has filled in every entry
+            // before the tail
+            unsafe {
+                let head = (*self.cq_head).load(Ordering::Relaxed);
+                if head != (*self.cq_tail).load(Ordering::Acquire) {
+                    let cqe = *self.cqes.add((head & self.cq_mask) as usize);
+                    (*self.cq_head).store(head.wrapping_add(1), Ordering::Release);
+                    self.in_flight -= 1;
+                    return Ok(cqe);
+                }
+            }
+
+            let ret = unsafe {
+                libc::syscall(
+                    libc::SYS_io_uring_enter,
+                    self.fd,
+                    self.to_submit,
+                    1u32,
+                    IORING_ENTER_GETEVENTS,
+                    ptr::null::<libc::sigset_t>(),
+                    0usize,
+                )
+            };
+            if ret < 0 {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            } else {
+                self.to_submit -= ret as u32;
+            }
+        }
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        // The kernel may still be writing into the buffers, so wait for every request before
+        // they can be freed
+        while self.in_flight > 0 {
+            if self.wait().is_err() {
+                break;
+            }
+        }
+
+        unsafe {
+            for &(ptr, len) in &self.maps {
+                libc::munmap(ptr, len);
+            }
+            libc::close(self.fd);
+        }
+    }
+}
+
+// Progress of the read into one of the buffers
+#[derive(Clone, Copy, Default)]
+struct Slot {
+    offset: u64,
+    filled: usize,
+    done: bool,
+}
+
+pub(crate) fn digest_path<P: AsRef<Path>>(path: P) -> Result<(Hash, u64), Sha1Error> {
+    let mut file = File::open(path)?;
+
+    // Declared before the ring so that it outlives every read into it
+    let mut buf = vec![0u8; DEPTH * BUF_LEN];
+    let base = buf.as_mut_ptr();
+
+    let mut ring = match Ring::new(DEPTH as u32) {
+        Ok(ring) => ring,
+        // io_uring is not supported by the kernel or is blocked, e.g. by a container's seccomp
+        // filter
+        Err(e) if matches!(e.raw_os_error(), Some(libc::ENOSYS) | Some(libc::EPERM)) => {
+            return FileHasher::new().digest_file(&mut file);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    // Registering can fail if the buffers exceed the locked memory limit. Plain reads still work
+    // then, just with more overhead per read.
+    let fixed = ring.register_buffers(base).is_ok();
+
+    let fd = file.as_raw_fd();
+    let read = |ring: &mut Ring, i: usize, slot: &Slot| {
+        ring.push(Sqe {
+            opcode: if fixed {
+                IORING_OP_READ_FIXED
+            } else {
+                IORING_OP_READ
+            },
+            fd,
+            off: slot.offset + slot.filled as u64,
+            addr: unsafe { base.add(i * BUF_LEN + slot.filled) } as u64,
+            len: (BUF_LEN - slot.filled) as u32,
+            user_data: i as u64,
+            buf_index: i as u16,
+            ..Sqe::default()
+        })
+    };
+
+    let mut slots = [Slot::default(); DEPTH];
+    for (i, slot) in slots.iter_mut().enumerate() {
+        slot.offset = (i * BUF_LEN) as u64;
+        read(&mut ring, i, slot);
+    }
+
+    let mut s = Sha1::new();
+    let mut bytes = 0;
+    let mut next = 0;
+
+    loop {
+        // Hash finished buffers in file order, reusing each one for the next read
+        while slots[next].done {
+            let slot = &mut slots[next];
+            // Safety: the read into this buffer has completed, and no other is in flight
+            let data = unsafe { slice::from_raw_parts(base.add(next * BUF_LEN), slot.filled) };
+            s.update(data);
+            bytes += slot.filled as u64;
+
+            // A buffer that isn't full was ended by end of file
+            if slot.filled < BUF_LEN {
+                return Ok((s.try_finish()?, bytes));
+            }
+
+            *slot = Slot {
+                offset: slot.offset + (DEPTH * BUF_LEN) as u64,
+                ..Slot::default()
+            };
+            read(&mut ring, next, slot);
+            next = (next + 1) % DEPTH;
+        }
+
+        let cqe = ring.wait()?;
+        let i = cqe.user_data as usize;
+        let slot = &mut slots[i];
+
+        if cqe.res < 0 {
+            let e = io::Error::from_raw_os_error(-cqe.res);
+            if e.kind() != io::ErrorKind::Interrupted && e.kind() != io::ErrorKind::WouldBlock {
+                return Err(e.into());
+            }
+        } else if cqe.res == 0 || slot.filled + cqe.res as usize == BUF_LEN {
+            slot.filled += cqe.res as usize;
+            slot.done = true;
+            continue;
+        } else {
+            slot.filled += cqe.res as usize;
+        }
+
+        // Retry, or continue a short read
+        read(&mut ring, i, slot);
+    }
+}
@@ -0,0 +1,45 @@
+// Message schedule using wasm32 simd128 instructions. Only compiled when the simd128 target
+// feature is enabled, e.g. with RUSTFLAGS="-C target-feature=+simd128".
+
+use core::arch::wasm32::*;
+
+// Fills the 80 word message schedule for a chunk.
+pub(crate) fn schedule(chunk: &[u8; 64], w: &mut [u32; 80]) {
+    // Load the first 16 words 4 at a time, reversing the bytes of each from big-endian
+    for i in 0..4 {
+        // Safety: reads 16 bytes at offset i * 16 of the 64 byte chunk, and writes 4 words at
+        // offset i * 4 of the 80 word schedule. wasm loads and stores don't need alignment.
+        unsafe {
+            let v = v128_load(chunk.as_ptr().add(i * 16) as *const v128);
+            let v = i8x16_shuffle::<3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12>(v, v);
+            v128_store(w.as_mut_ptr().add(i * 4) as *mut v128, v);
+        }
+    }
+
+    // Each word here depends on the word 3 before it, so they can't be computed 4 at a time
+    for i in 16..32 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    // With this form of the recurrence, every word depends only on words at least 6 before it,
+    // so 4 words at a time can be computed at once
+    for i in (32..80).step_by(4) {
+        // Safety: every load reads 4 words before index i, and the store writes words i..i + 4,
+        // which is within the schedule since i is at most 76.
+        unsafe {
+            let p = w.as_ptr();
+            let x = v128_xor(
+                v128_xor(
+                    v128_load(p.add(i - 6) as *const v128),
+                    v128_load(p.add(i - 16) as *const v128),
+                ),
+                v128_xor(
+                    v128_load(p.add(i - 28) as *const v128),
+                    v128_load(p.add(i - 32) as *const v128),
+                ),
+            );
+            let x = v128_or(u32x4_shl(x, 2), u32x4_shr(x, 30));
+            v128_store(w.as_mut_ptr().add(i) as *mut v128, x);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Hash, Sha1, Sha1Error};
+use std::io::{self, Write};
+
+/// Writer adapter that forwards writes to an inner writer and hashes all bytes that the inner
+/// writer accepted.
+///
+/// Example usage:
+/// ```
+/// # use sha1::HashingWriter;
+/// # use std::fs::File;
+/// # fn f(data: &[u8]) -> std::io::Result<()> {
+/// // Write a file and record its hash at the same time
+/// let mut writer = HashingWriter::new(File::create("foo.txt")?);
+/// std::io::copy(&mut &data[..], &mut writer)?;
+/// let (file, hash) = writer.finish();
+/// # Ok(())
+/// # }
+/// ```
+pub struct HashingWriter<W> {
+    inner: W,
+    sha1: Sha1,
+    bytes: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    /// Creates a new `HashingWriter` that writes to `inner`.
+    pub fn new(inner: W) -> HashingWriter<W> {
+        HashingWriter {
+            inner,
+            sha1: Sha1::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Returns the hash of all bytes written so far. Writing may continue afterwards.
+    pub fn digest(&self) -> Hash {
+        self.sha1.peek_finish()
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer. Bytes written directly to the inner
+    /// writer are not hashed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps the inner writer and returns it along with the hash of all bytes written.
+    /// The inner writer is not flushed.
+    pub fn finish(self) -> (W, Hash) {
+        (self.inner, self.sha1.finish())
+    }
+
+    /// Unwraps the inner writer, discarding the hash.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    /// Writes to the inner writer and hashes the bytes that it accepted.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(data)?;
+        self.sha1.update(&data[..n]);
+        self.bytes += n as u64;
+        Ok(n)
+    }
+
+    /// Flushes the inner writer.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writer adapter that hashes all bytes written through it, and checks on `finish()` that their
+/// hash is the expected one.
+///
+/// Example usage:
+/// ```
+/// # use sha1::VerifyingWriter;
+/// # use std::fs::File;
+/// # fn f<R: std::io::Read>(mut upload: R, declared: [u32; 5]) -> std::io::Result<()> {
+/// // Receive an upload, failing if it doesn't match the checksum the client declared
+/// let mut writer = VerifyingWriter::new(File::create("upload.bin")?, declared);
+/// std::io::copy(&mut upload, &mut writer)?;
+/// let file = writer.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct VerifyingWriter<W> {
+    inner: HashingWriter<W>,
+    expected: Hash,
+}
+
+impl<W: Write> VerifyingWriter<W> {
+    /// Creates a new `VerifyingWriter` that writes to `inner` and expects the written data to
+    /// have the hash `expected`.
+    pub fn new(inner: W, expected: Hash) -> VerifyingWriter<W> {
+        VerifyingWriter {
+            inner: HashingWriter::new(inner),
+            expected,
+        }
+    }
+
+    /// Compares the hash of all bytes written so far to the expected hash.
+    pub fn verify(&self) -> Result<(), Sha1Error> {
+        let actual = self.inner.digest();
+        if actual == self.expected {
+            Ok(())
+        } else {
+            Err(Sha1Error::Mismatch {
+                expected: self.expected,
+                actual,
+            })
+        }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.inner.bytes_written()
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        self.inner.get_ref()
+    }
+
+    /// Flushes and unwraps the inner writer if the hash of all bytes written matches the
+    /// expected hash. Otherwise returns `Sha1Error::Mismatch`, and the inner writer is dropped.
+    pub fn finish(mut self) -> Result<W, Sha1Error> {
+        self.inner.flush()?;
+        self.verify()?;
+        Ok(self.inner.into_inner())
+    }
+
+    /// Unwraps the inner writer, without verifying the data.
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+}
+
+impl<W: Write> Write for VerifyingWriter<W> {
+    /// Writes to the inner writer and hashes the bytes that it accepted.
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.inner.write(data)
+    }
+
+    /// Flushes the inner writer.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,151 @@
+// Tests of the sha1sum binary, run against small files in a fresh directory for each test.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const ABC: &str = "a9993e364706816aba3e25717850c26c9cd0d89d";
+const EMPTY: &str = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+
+// Creates an empty directory for one test's files
+fn fixture(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sha1sum-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Runs sha1sum in dir with the given arguments and standard input
+fn sha1sum(dir: &PathBuf, args: &[&str], stdin: &[u8]) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sha1sum"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    let Output {
+        status,
+        stdout,
+        stderr,
+    } = child.wait_with_output().unwrap();
+
+    (
+        status.code().unwrap(),
+        String::from_utf8(stdout).unwrap(),
+        String::from_utf8(stderr).unwrap(),
+    )
+}
+
+#[test]
+fn output_formats_test() {
+    // Test GNU, GNU binary, and BSD output for files and standard input
+    let dir = fixture("formats");
+    fs::write(dir.join("a.txt"), "abc").unwrap();
+    fs::write(dir.join("empty"), "").unwrap();
+
+    let (code, out, _) = sha1sum(&dir, &["a.txt", "-", "empty"], b"abc");
+    assert!(code == 0);
+    assert!(out == format!("{}  a.txt\n{}  -\n{}  empty\n", ABC, ABC, EMPTY));
+
+    let (code, out, _) = sha1sum(&dir, &["-b", "a.txt"], b"");
+    assert!(code == 0 && out == format!("{} *a.txt\n", ABC));
+
+    let (code, out, _) = sha1sum(&dir, &["--tag", "a.txt"], b"");
+    assert!(code == 0 && out == format!("SHA1 (a.txt) = {}\n", ABC));
+
+    // With no files, standard input is hashed
+    let (code, out, _) = sha1sum(&dir, &[], b"");
+    assert!(code == 0 && out == format!("{}  -\n", EMPTY));
+
+    // Arguments after -- are files, even if they look like options
+    fs::write(dir.join("-c"), "abc").unwrap();
+    let (code, out, _) = sha1sum(&dir, &["--", "-c"], b"");
+    assert!(code == 0 && out == format!("{}  -c\n", ABC));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unreadable_file_test() {
+    // Test that files that can't be read are reported, and the rest are still hashed
+    let dir = fixture("unreadable");
+    fs::write(dir.join("a.txt"), "abc").unwrap();
+
+    let (code, out, err) = sha1sum(&dir, &["missing", "a.txt"], b"");
+    assert!(code == 1);
+    assert!(out == format!("{}  a.txt\n", ABC));
+    assert!(err.starts_with("sha1sum: missing: "));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_test() {
+    // Test that --check reads both line formats, from files and standard input, and exits with
+    // 0 when everything matches
+    let dir = fixture("check");
+    fs::write(dir.join("a.txt"), "abc").unwrap();
+    fs::write(dir.join("empty"), "").unwrap();
+    let sums = format!("{}  a.txt\nSHA1 (empty) = {}\n", ABC, EMPTY);
+    fs::write(dir.join("sums"), &sums).unwrap();
+
+    let (code, out, err) = sha1sum(&dir, &["--check", "sums"], b"");
+    assert!(code == 0 && err.is_empty());
+    assert!(out == "a.txt: OK\nempty: OK\n");
+
+    let (code, out, _) = sha1sum(&dir, &["-c"], sums.as_bytes());
+    assert!(code == 0 && out == "a.txt: OK\nempty: OK\n");
+
+    // Improperly formatted lines only produce a warning
+    let (code, out, err) = sha1sum(&dir, &["-c", "-"], format!("junk\n{}", sums).as_bytes());
+    assert!(code == 0 && out == "a.txt: OK\nempty: OK\n");
+    assert!(err == "sha1sum: WARNING: 1 line(s) are improperly formatted\n");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_failures_test() {
+    // Test that mismatched and missing files are reported and make --check exit with 1
+    let dir = fixture("check-failures");
+    fs::write(dir.join("a.txt"), "abd").unwrap();
+    let sums = format!("{}  a.txt\n{}  missing\n", ABC, ABC);
+    fs::write(dir.join("sums"), sums).unwrap();
+
+    let (code, out, err) = sha1sum(&dir, &["-c", "sums"], b"");
+    assert!(code == 1);
+    assert!(out == "a.txt: FAILED\nmissing: FAILED open or read\n");
+    assert!(err.contains("sha1sum: WARNING: 1 listed file(s) could not be read\n"));
+    assert!(err.contains("sha1sum: WARNING: 1 computed checksum(s) did NOT match\n"));
+
+    // A checksum file with no checksums in it is an error
+    fs::write(dir.join("junk"), "not a checksum\n").unwrap();
+    let (code, _, err) = sha1sum(&dir, &["-c", "junk"], b"");
+    assert!(code == 1);
+    assert!(err.contains("sha1sum: junk: no properly formatted checksum lines found\n"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn options_test() {
+    // Test that --help exits with 0, and that unknown options exit with 2
+    let dir = fixture("options");
+
+    let (code, out, _) = sha1sum(&dir, &["--help"], b"");
+    assert!(code == 0 && out.starts_with("Usage: sha1sum"));
+
+    let (code, out, err) = sha1sum(&dir, &["--bogus"], b"");
+    assert!(code == 2 && out.is_empty());
+    assert!(err.starts_with("sha1sum: unrecognized option '--bogus'"));
+
+    let (code, _, err) = sha1sum(&dir, &["-bx"], b"");
+    assert!(code == 2 && err.starts_with("sha1sum: invalid option -- 'x'"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}